@@ -0,0 +1,43 @@
+use programming_language::driver::diag::{BufferSink, DiagPrinter};
+use programming_language::syntax::lexer::Lexer;
+use programming_language::syntax::token::TokenType;
+use std::time::{Duration, Instant};
+
+const MEGABYTE: usize = 1024 * 1024;
+
+// Repeats a small program (with some multi byte UTF-8 in it) until the input is at least `size` bytes
+fn generate_source(size: usize) -> String {
+    let mut source: String = String::with_capacity(size + 256);
+    let mut index: usize = 0;
+    while source.len() < size {
+        source += &format!("// générée automatiquement #{}\nfunc function_{}(): int {{\n    std::println(\"Grüße, {}\");\n    return (34+35);\n}}\n", index, index, index);
+        index += 1;
+    }
+    source
+}
+
+fn lex_all(source: String) -> (usize, Duration) {
+    let diag: DiagPrinter = DiagPrinter::new(false, false, "bench.comp".to_string(), source.clone(), 0).with_sink(Box::new(BufferSink::new()));
+    let mut lexer: Lexer = Lexer::new(source, diag.clone());
+    let start: Instant = Instant::now();
+    let mut tokens: usize = 0;
+    while lexer.next_token().get_type() != TokenType::Eof {
+        tokens += 1;
+    }
+    let elapsed: Duration = start.elapsed();
+    assert!(!diag.has_errors(), "generated source should lex without errors");
+    (tokens, elapsed)
+}
+
+fn main() {
+    println!("{:>8} {:>10} {:>12} {:>10} {:>10}", "size", "tokens", "time", "MB/s", "ns/byte");
+    for megabytes in [1, 2, 4, 8] {
+        let source: String = generate_source(megabytes * MEGABYTE);
+        let bytes: usize = source.len();
+        let (tokens, elapsed) = lex_all(source);
+        let throughput: f64 = bytes as f64 / MEGABYTE as f64 / elapsed.as_secs_f64();
+        // Linear lexing keeps the cost per byte roughly constant across sizes
+        let nanos_per_byte: f64 = elapsed.as_nanos() as f64 / bytes as f64;
+        println!("{:>6}MB {:>10} {:>12.2?} {:>10.1} {:>10.2}", megabytes, tokens, elapsed, throughput, nanos_per_byte);
+    }
+}
@@ -1,4 +1,7 @@
 fn main() {
+    // cc emits its own rerun-if lines, which would otherwise hide edits to the sources
+    println!("cargo:rerun-if-changed=src/driver/args.cc");
+    println!("cargo:rerun-if-changed=include/clopts.hh");
     cc::Build::new()
         .cpp(true)  // Enable C++ compilation
         .include("include")
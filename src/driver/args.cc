@@ -11,6 +11,7 @@ using options = clopts<
     flag<"-v", "Enable verbose printing">,
     option<"-o", "Outfile file", std::string>,
     option<"--color", "Use colors", values<"always", "never", "default">>,
+    option<"--error-limit", "Stop reporting errors after this many (0 for no limit)", int64_t>,
     help<>
 >;
 
@@ -19,6 +20,7 @@ struct Args{
     const uint8_t* out_file;
     uint8_t file_paths_count;
     const uint8_t** file_paths;
+    uint32_t error_limit;
 };
 
 extern "C" Args* getArgs(int argc, char** argv) {
@@ -31,6 +33,11 @@ extern "C" Args* getArgs(int argc, char** argv) {
     verbose = opts.get<"-v">();
     std::string colorOpt = opts.get_or<"--color">("always");
     useColors = colorOpt == "always";
+    int64_t errorLimit = opts.get_or<"--error-limit">(20);
+    if(errorLimit < 0){
+        std::printf("ERROR: --error-limit must not be negative\n");
+        std::exit(1);
+    }
     if(file_paths.empty()){
         std::printf("ERROR: No file paths provided\n");
         std::exit(1);
@@ -38,6 +45,7 @@ extern "C" Args* getArgs(int argc, char** argv) {
     Args* args = reinterpret_cast<Args*>(malloc(sizeof(Args)));
     args->verbose = verbose;
     args->useColors = useColors;
+    args->error_limit = static_cast<uint32_t>(errorLimit);
     args->out_file = new uint8_t[out_file.size() + 1];
     args->file_paths = new const uint8_t*[file_paths.size()];
     args->file_paths_count = 0;
@@ -17,12 +17,19 @@ impl Context{
             self.diagnostic_printer.print_formatted(DiagType::Debug, format!("File data: `{}`", self.file_data));
         }
     }
-    pub fn run(&self){
+    // Runs every phase, stopping after the first one that reported errors. Returns false on failure
+    pub fn run(&self) -> bool {
         let lexer: Lexer = Lexer::new(self.file_data.clone(), self.diagnostic_printer.clone());
         let mut parser: Parser = Parser::new(lexer, self.diagnostic_printer.clone());
         let ast: Ast = parser.parse_to_ast();
-        ast.print();
+        if self.diagnostic_printer.has_errors() {
+            return self.diagnostic_printer.finish();
+        }
+        if self.diagnostic_printer.is_verbose() {
+            ast.print();
+        }
         let sema: Sema = Sema::new(ast, self.diagnostic_printer.clone());
         sema.run();
+        self.diagnostic_printer.finish()
     }
 }
\ No newline at end of file
@@ -1,13 +1,62 @@
-use std::process::exit;
+use std::cell::RefCell;
+use std::fmt::Debug;
+use std::rc::Rc;
+use crate::syntax::span::Span;
 
+// Destination for rendered diagnostics
+pub trait DiagSink: Debug {
+    fn write(&mut self, text: &str);
+}
+
+#[derive(Debug, Default)]
+pub struct StderrSink;
+
+impl DiagSink for StderrSink {
+    fn write(&mut self, text: &str) {
+        eprint!("{}", text);
+    }
+}
+
+// Keeps diagnostics in memory, clones share the same buffer so it can be read after compiling
+#[derive(Debug, Clone, Default)]
+pub struct BufferSink {
+    buffer: Rc<RefCell<String>>,
+}
+
+impl BufferSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn contents(&self) -> String {
+        self.buffer.borrow().clone()
+    }
+}
+
+impl DiagSink for BufferSink {
+    fn write(&mut self, text: &str) {
+        self.buffer.borrow_mut().push_str(text);
+    }
+}
+
+#[derive(Debug)]
+struct DiagState {
+    sink: Box<dyn DiagSink>,
+    error_count: usize,
+    warning_count: usize,
+    error_limit: usize, // 0 means no limit
+}
+
+// Cheap to clone, every clone reports into the same counters and sink
 #[derive(Clone, Debug)]
 pub struct DiagPrinter {
     verbose: bool,
     use_colors: bool,
-    file_path: String
+    file_path: String,
+    source: Rc<str>,
+    state: Rc<RefCell<DiagState>>,
 }
 
-#[derive(PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum DiagType {
     Ice,
     Error,
@@ -24,9 +73,81 @@ const GREEN: &str = "\x1b[32m";
 const MAGENTA: &str = "\x1b[35m";
 const CYAN: &str = "\x1b[36m";
 
+// Spans covering more lines than this only show their first lines
+const MAX_SNIPPET_LINES: usize = 4;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    span: Span,
+    message: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    diag_type: DiagType,
+    message: String,
+    span: Option<Span>,
+    labels: Vec<Label>,
+}
+
+impl Diagnostic {
+    pub fn new(diag_type: DiagType, message: String) -> Self {
+        Self { diag_type, message, span: None, labels: vec![] }
+    }
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+    // Secondary location shown below the primary one, e.g. "previous definition here"
+    pub fn with_label(mut self, span: Span, message: String) -> Self {
+        self.labels.push(Label { span, message });
+        self
+    }
+}
+
+// One underlined segment of a single source line
+struct Marker<'a> {
+    line: usize,
+    line_start: usize,
+    start: usize,
+    end: usize,
+    primary: bool,
+    message: Option<&'a str>,
+}
+
 impl DiagPrinter {
-    pub fn new(verbose: bool, use_colors: bool, file_path: String) -> Self {
-        Self { verbose, use_colors, file_path }
+    pub fn new(verbose: bool, use_colors: bool, file_path: String, source: String, error_limit: usize) -> Self {
+        let state: DiagState = DiagState { sink: Box::new(StderrSink), error_count: 0, warning_count: 0, error_limit };
+        Self { verbose, use_colors, file_path, source: Rc::from(source), state: Rc::new(RefCell::new(state)) }
+    }
+    pub fn with_sink(self, sink: Box<dyn DiagSink>) -> Self {
+        self.state.borrow_mut().sink = sink;
+        self
+    }
+    pub fn error_count(&self) -> usize {
+        self.state.borrow().error_count
+    }
+    pub fn warning_count(&self) -> usize {
+        self.state.borrow().warning_count
+    }
+    pub fn is_verbose(&self) -> bool {
+        self.verbose
+    }
+    pub fn has_errors(&self) -> bool {
+        self.error_count() > 0
+    }
+    pub fn limit_reached(&self) -> bool {
+        let state = self.state.borrow();
+        state.error_limit != 0 && state.error_count >= state.error_limit
+    }
+    // Prints the "N errors, M warnings" line, returns false if any error was reported
+    pub fn finish(&self) -> bool {
+        let (errors, warnings): (usize, usize) = (self.error_count(), self.warning_count());
+        if errors > 0 || warnings > 0 {
+            let summary: String = format!("{} error{}, {} warning{}\n", errors, if errors == 1 { "" } else { "s" }, warnings, if warnings == 1 { "" } else { "s" });
+            self.state.borrow_mut().sink.write(&summary);
+        }
+        errors == 0
     }
     pub fn print_info(&self){
         if self.verbose {
@@ -52,7 +173,10 @@ impl DiagPrinter {
                 }
             }
         }
-        RESET.to_owned()
+        String::new()
+    }
+    fn get_reset(&self) -> &'static str {
+        if self.use_colors { RESET } else { "" }
     }
     fn get_name(&self, diag_type: &DiagType) -> String {
         match diag_type {
@@ -77,12 +201,94 @@ impl DiagPrinter {
         }
     }
     pub fn print_formatted(&self, diag_type: DiagType, format: String) {
+        self.emit(Diagnostic::new(diag_type, format));
+    }
+    pub fn print_formatted_at(&self, diag_type: DiagType, span: Span, format: String) {
+        self.emit(Diagnostic::new(diag_type, format).with_span(span));
+    }
+    // Splits `span` into one marker per source line it covers
+    fn collect_markers<'a>(&self, markers: &mut Vec<Marker<'a>>, span: Span, primary: bool, message: Option<&'a str>) {
+        let source: &str = &self.source;
+        let mut start: usize = span.start.min(source.len());
+        let end: usize = span.end.clamp(start, source.len());
+        for (line, shown) in (span.line..).zip(1..=MAX_SNIPPET_LINES) {
+            let line_start: usize = source[..start].rfind('\n').map_or(0, |index| index + 1);
+            let line_end: usize = source[start..].find('\n').map_or(source.len(), |index| start + index);
+            let last: bool = end <= line_end || shown == MAX_SNIPPET_LINES;
+            markers.push(Marker { line, line_start, start, end: end.min(line_end), primary, message: if last { message } else { None } });
+            if last {
+                break;
+            }
+            // Continuation lines are underlined from their first non-blank character
+            start = line_end + 1;
+            start += source[start..end].len() - source[start..end].trim_start_matches([' ', '\t']).len();
+        }
+    }
+    fn render_snippet(&self, diagnostic: &Diagnostic) -> String {
+        let mut markers: Vec<Marker> = vec![];
+        if let Some(span) = diagnostic.span {
+            self.collect_markers(&mut markers, span, true, None);
+        }
+        for label in &diagnostic.labels {
+            self.collect_markers(&mut markers, label.span, false, Some(&label.message));
+        }
+        markers.sort_by_key(|marker| marker.line);
+        let width: usize = markers.iter().map(|marker| marker.line.to_string().len()).max().unwrap_or(0);
+        let mut snippet: String = String::new();
+        let mut previous_line: Option<usize> = None;
+        for marker in &markers {
+            let source: &str = &self.source;
+            if previous_line != Some(marker.line) {
+                let line_end: usize = source[marker.line_start..].find('\n').map_or(source.len(), |index| marker.line_start + index);
+                let text: &str = source[marker.line_start..line_end].trim_end_matches('\r');
+                snippet += &format!("{:>width$} | {}\n", marker.line, text, width = width);
+                previous_line = Some(marker.line);
+            }
+            // Keep tabs so the underline lines up with the source text
+            let padding: String = source[marker.line_start..marker.start].chars().map(|c| if c == '\t' { '\t' } else { ' ' }).collect();
+            let length: usize = source[marker.start..marker.end].chars().count().max(1);
+            let (color, underline): (String, String) = if marker.primary {
+                (self.get_color(&diagnostic.diag_type), "^".to_string() + &"~".repeat(length - 1))
+            } else {
+                (self.get_color(&DiagType::Note), "-".repeat(length))
+            };
+            snippet += &format!("{:>width$} | {}{}{}", "", padding, color, underline, width = width);
+            if let Some(message) = marker.message {
+                snippet += &format!(" {}", message);
+            }
+            snippet += self.get_reset();
+            snippet += "\n";
+        }
+        snippet
+    }
+    // The location, type and message of a diagnostic followed by its source snippet
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let location: String = match diagnostic.span {
+            Some(span) => format!("{}:{}:{}: ", self.file_path, span.line, span.column),
+            None => String::new(),
+        };
+        let text: String = format!("{}{}{}: {}{}\n", location, self.get_color(&diagnostic.diag_type), self.get_name(&diagnostic.diag_type), self.get_reset(), diagnostic.message);
+        text + &self.render_snippet(diagnostic)
+    }
+    pub fn emit(&self, diagnostic: Diagnostic) {
+        let diag_type: DiagType = diagnostic.diag_type.clone();
         if diag_type == DiagType::Debug && !self.verbose {
             return;
         }
-        println!("{}{}: {}{}", self.get_color(&diag_type), self.get_name(&diag_type), RESET, format);
-        if diag_type == DiagType::Ice || diag_type == DiagType::Error {
-            exit(1);
+        let is_error: bool = diag_type == DiagType::Ice || diag_type == DiagType::Error;
+        if is_error && self.limit_reached() {
+            return;
+        }
+        let mut text: String = self.render(&diagnostic);
+        let mut state = self.state.borrow_mut();
+        match diag_type {
+            DiagType::Ice | DiagType::Error => state.error_count += 1,
+            DiagType::Warning => state.warning_count += 1,
+            _ => {}
+        }
+        if is_error && state.error_limit != 0 && state.error_count >= state.error_limit {
+            text += &format!("{}{}: {}too many errors emitted, stopping now [--error-limit]\n", self.get_color(&DiagType::Note), self.get_name(&DiagType::Note), self.get_reset());
         }
+        state.sink.write(&text);
     }
 }
\ No newline at end of file
@@ -1,6 +1,3 @@
-pub mod driver;
-pub mod syntax;
-pub mod sema;
 use std::ptr;
 use std::str;
 use std::str::Utf8Error;
@@ -8,8 +5,9 @@ use std::slice;
 use std::ffi::CStr;
 use std::fs;
 use std::thread;
-use driver::context::Context;
-use driver::diag::DiagPrinter;
+use std::process::exit;
+use programming_language::driver::context::Context;
+use programming_language::driver::diag::DiagPrinter;
 use std::error::Error;
 
 #[repr(C)]
@@ -19,6 +17,7 @@ struct Args{
     out_file: *const u8,
     file_paths_count: u8,
     file_paths: *const *const u8,
+    error_limit: u32,
 }
 
 unsafe extern "C" {
@@ -64,24 +63,32 @@ fn real_main() -> Result<(), Box<dyn Error + Send>> {
     let args: Args;
     let verbose: bool;
     let use_colors: bool;
+    let error_limit: usize;
     let mut file_paths: Vec<String> = vec![];
     let _out_file: String;
     unsafe {
         args = ptr::read_unaligned(getArgs(c_args.len() as i32, c_args.as_ptr()));
         verbose = args.verbose;
         use_colors = args.use_colors;
+        error_limit = args.error_limit as usize;
         let file_paths_slice: &[*const u8] = std::slice::from_raw_parts(args.file_paths, args.file_paths_count as usize);
         for path in file_paths_slice {
             file_paths.push(convert(str::from_utf8(slice::from_raw_parts(*path, CStr::from_ptr(*path as *const i8).to_bytes().len()))));
         }
         _out_file = convert(str::from_utf8(slice::from_raw_parts(args.out_file, CStr::from_ptr(args.out_file as *const i8).to_bytes().len())));
     }
+    let mut failed: bool = false;
     for file_path in file_paths {
-        let diagnostic_printer: DiagPrinter = DiagPrinter::new(verbose, use_colors, file_path.clone());
         let file_contents: String = fs::read_to_string(file_path.clone()).unwrap();
+        let diagnostic_printer: DiagPrinter = DiagPrinter::new(verbose, use_colors, file_path.clone(), file_contents.clone(), error_limit);
         let ctx: Context = Context::new(diagnostic_printer, file_contents);
         ctx.print_info(true);
-        ctx.run();
+        if !ctx.run() {
+            failed = true;
+        }
+    }
+    if failed {
+        exit(1);
     }
     Ok(())
 }
@@ -1,6 +1,6 @@
 use crate::{driver::diag::DiagPrinter, syntax::ast::Ast};
 
-use super::checking::sema::SemaChecker;
+use super::{checking::sema::SemaChecker, hir::Hir};
 
 pub struct Sema {
     ast: Ast,
@@ -11,9 +11,13 @@ impl Sema {
     pub fn new(ast: Ast, diag: DiagPrinter) -> Self {
         Self { ast, diag }
     }
-    pub fn run(&self) -> Ast {
+    // The typed program, `None` if checking reported errors or lowering ran into an ICE
+    pub fn run(&self) -> Option<Hir> {
         let mut sema_checker: SemaChecker = SemaChecker::new(self.ast.clone(), self.diag.clone());
         sema_checker.check();
-        self.ast.clone()
+        if self.diag.has_errors() {
+            return None;
+        }
+        sema_checker.lower()
     }
 }
\ No newline at end of file
@@ -0,0 +1,141 @@
+use crate::syntax::{ast::{ExprType, StatementType}, span::Span, token::TokenType};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NodeKind {
+    Entry,
+    Exit, // Reached by every `return` and by falling off the end
+    End, // The end of the body, reaching it means falling off the end of the function
+    Statement,
+    Return,
+    Branch, // The condition of an `if` or a loop, or the value a `match` statement matches
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub kind: NodeKind,
+    pub span: Span,
+    pub successors: Vec<usize>,
+}
+
+// Control flow graph of a function body with a node for every statement, branches have an
+// edge to each way they can continue. Loops run any number of times, `while (true)` never ends
+#[derive(Debug, Clone, PartialEq)]
+pub struct ControlFlowGraph {
+    pub nodes: Vec<Node>,
+    sequences: Vec<Vec<(usize, Span)>>, // The first node of each statement and its span, per list of statements in a block
+    non_exhaustive_matches: HashSet<usize>, // Ids of the `match`es that don't cover every value
+}
+
+const ENTRY: usize = 0;
+const EXIT: usize = 1;
+
+impl ControlFlowGraph {
+    pub fn build(body: &StatementType, non_exhaustive_matches: &HashSet<usize>) -> Self {
+        let mut graph: Self = Self { nodes: vec![], sequences: vec![], non_exhaustive_matches: non_exhaustive_matches.clone() };
+        graph.add(NodeKind::Entry, body.span(), vec![]);
+        graph.add(NodeKind::Exit, body.span(), vec![]);
+        let falls_through: Vec<usize> = graph.lower(body, vec![ENTRY]);
+        let end: usize = graph.add(NodeKind::End, body.span(), falls_through);
+        graph.nodes[end].successors.push(EXIT);
+        graph
+    }
+    // Adds a node that `predecessors` continue to
+    fn add(&mut self, kind: NodeKind, span: Span, predecessors: Vec<usize>) -> usize {
+        let id: usize = self.nodes.len();
+        self.nodes.push(Node { kind, span, successors: vec![] });
+        for predecessor in predecessors {
+            self.nodes[predecessor].successors.push(id);
+        }
+        id
+    }
+    // Adds the nodes of `stmt` after `predecessors`, returns the nodes execution continues from afterwards
+    fn lower(&mut self, stmt: &StatementType, predecessors: Vec<usize>) -> Vec<usize> {
+        match stmt {
+            StatementType::Block(block) => {
+                let start: usize = self.add(NodeKind::Statement, block.span(), predecessors);
+                self.lower_sequence(&block.body, vec![start])
+            }
+            StatementType::Return(_, span) => {
+                let node: usize = self.add(NodeKind::Return, *span, predecessors);
+                self.nodes[node].successors.push(EXIT);
+                vec![]
+            }
+            StatementType::If(if_stmt) => {
+                let branch: usize = self.add(NodeKind::Branch, if_stmt.condition().span(), predecessors);
+                let mut falls_through: Vec<usize> = self.lower(&if_stmt.then_branch(), vec![branch]);
+                match if_stmt.else_branch() {
+                    Some(else_branch) => falls_through.extend(self.lower(&else_branch, vec![branch])),
+                    None => falls_through.push(branch),
+                }
+                falls_through
+            }
+            StatementType::While(while_stmt) => {
+                let branch: usize = self.add(NodeKind::Branch, while_stmt.condition().span(), predecessors);
+                for last in self.lower(&while_stmt.body(), vec![branch]) {
+                    self.nodes[last].successors.push(branch);
+                }
+                let endless: bool = matches!(while_stmt.condition(), ExprType::BoolLiteral(token, _) if token.get_type() == TokenType::True);
+                if endless { vec![] } else { vec![branch] }
+            }
+            StatementType::For(for_stmt) => {
+                let branch: usize = self.add(NodeKind::Branch, for_stmt.iterable().span(), predecessors);
+                for last in self.lower(&for_stmt.body(), vec![branch]) {
+                    self.nodes[last].successors.push(branch);
+                }
+                vec![branch]
+            }
+            // Arms are only reached through the value, a `match` without arms never continues.
+            // One that isn't exhaustive can also skip every arm
+            StatementType::Expr(ExprType::Match(match_expr), _) => {
+                let branch: usize = self.add(NodeKind::Branch, match_expr.scrutinee().span(), predecessors);
+                let mut falls_through: Vec<usize> = match_expr.arms().iter().flat_map(|arm| self.lower(&arm.body(), vec![branch])).collect();
+                if self.non_exhaustive_matches.contains(&match_expr.get_id()) {
+                    falls_through.push(branch);
+                }
+                falls_through
+            }
+            _ => vec![self.add(NodeKind::Statement, stmt.span(), predecessors)],
+        }
+    }
+    fn lower_sequence(&mut self, stmts: &[StatementType], predecessors: Vec<usize>) -> Vec<usize> {
+        let mut starts: Vec<(usize, Span)> = vec![];
+        let mut current: Vec<usize> = predecessors;
+        for stmt in stmts {
+            starts.push((self.nodes.len(), stmt.span()));
+            current = self.lower(stmt, current);
+        }
+        self.sequences.push(starts);
+        current
+    }
+    fn reachable(&self) -> Vec<bool> {
+        let mut reached: Vec<bool> = vec![false; self.nodes.len()];
+        let mut pending: Vec<usize> = vec![ENTRY];
+        while let Some(node) = pending.pop() {
+            if reached[node] {
+                continue;
+            }
+            reached[node] = true;
+            pending.extend(self.nodes[node].successors.iter().copied());
+        }
+        reached
+    }
+    // Whether some path through the body ends without a `return`
+    pub fn falls_off_end(&self) -> bool {
+        let reached: Vec<bool> = self.reachable();
+        self.nodes.iter().zip(reached).any(|(node, reached)| node.kind == NodeKind::End && reached)
+    }
+    // The first statement of each unreachable run of statements and the reachable one before it,
+    // statements nested in one that is unreachable aren't reported again
+    pub fn unreachable(&self) -> Vec<(Span, Span)> {
+        let reached: Vec<bool> = self.reachable();
+        let mut found: Vec<(Span, Span)> = vec![];
+        for starts in &self.sequences {
+            if let Some(pair) = starts.windows(2).find(|pair| reached[pair[0].0] && !reached[pair[1].0]) {
+                found.push((pair[1].1, pair[0].1));
+            }
+        }
+        found.sort_by_key(|(span, _)| span.start);
+        found
+    }
+}
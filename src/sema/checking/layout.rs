@@ -0,0 +1,61 @@
+use crate::syntax::ast::TypeSpec;
+
+// Size and alignment of a type in bytes, structs also record where each field starts
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    pub size: usize,
+    pub align: usize,
+    pub field_offsets: Vec<usize>, // In declaration order, for enums the tag and the payload, empty for everything else
+}
+
+fn align_to(offset: usize, align: usize) -> usize {
+    offset.div_ceil(align) * align
+}
+
+impl Layout {
+    pub fn scalar(size: usize, align: usize) -> Self {
+        Self { size, align, field_offsets: vec![] }
+    }
+    // C layout: fields stay in declaration order, each one is placed at the next offset
+    // matching its alignment and the size is padded to a multiple of the largest alignment
+    pub fn of_struct(fields: &[Layout]) -> Self {
+        let mut offset: usize = 0;
+        let mut align: usize = 1;
+        let mut field_offsets: Vec<usize> = vec![];
+        for field in fields {
+            offset = align_to(offset, field.align);
+            field_offsets.push(offset);
+            offset += field.size;
+            align = align.max(field.align);
+        }
+        Self { size: align_to(offset, align), align, field_offsets }
+    }
+    // A tagged union: the smallest unsigned integer that can number every variant, followed by
+    // room for the largest payload. `payloads` are the variants' fields laid out as structs
+    pub fn of_enum(payloads: &[Layout]) -> Self {
+        let tag: usize = match payloads.len() {
+            0..=256 => 1,
+            257..=65536 => 2,
+            _ => 4,
+        };
+        let payload_align: usize = payloads.iter().map(|payload| payload.align).max().unwrap_or(1);
+        let payload_size: usize = payloads.iter().map(|payload| payload.size).max().unwrap_or(0);
+        let payload_offset: usize = align_to(tag, payload_align);
+        let align: usize = tag.max(payload_align);
+        Self { size: align_to(payload_offset + payload_size, align), align, field_offsets: vec![0, payload_offset] }
+    }
+}
+
+// Layout of the builtin types, `None` for types that need the checker to be laid out
+pub fn builtin_layout(type_spec: &TypeSpec) -> Option<Layout> {
+    match type_spec.canonical() {
+        TypeSpec::Int | TypeSpec::I64 | TypeSpec::U64 | TypeSpec::F64 => Some(Layout::scalar(8, 8)),
+        TypeSpec::I32 | TypeSpec::U32 | TypeSpec::F32 => Some(Layout::scalar(4, 4)),
+        TypeSpec::I16 | TypeSpec::U16 => Some(Layout::scalar(2, 2)),
+        TypeSpec::I8 | TypeSpec::U8 | TypeSpec::Bool => Some(Layout::scalar(1, 1)),
+        TypeSpec::String => Some(Layout::scalar(16, 8)), // Pointer to the bytes and the length
+        TypeSpec::Char => Some(Layout::scalar(1, 1)),
+        TypeSpec::Pointer(_) => Some(Layout::scalar(8, 8)),
+        _ => None,
+    }
+}
@@ -0,0 +1,309 @@
+use crate::{driver::diag::DiagType, sema::hir::{Arm, Expr, ExprKind, Function, Hir, Pattern, Stmt}, syntax::{ast::{DeclerationType, ExprType, FunctionDeclerationStatement, MatchExpression, Pattern as AstPattern, StatementType, TypeSpec}, lexer::{float_literal_value, integer_literal_value}, span::Span, token::TokenType}};
+use super::{sema::{DefinitionId, SemaChecker, SymbolKind}, types::is_assignable};
+
+// Lowering walks the program again with the scopes checking entered, evaluating types the same way.
+// It only runs on programs that checked without errors, so evaluating reports nothing a second time.
+// Something checking should have ruled out is reported as an ICE and stops lowering
+impl SemaChecker {
+    pub fn lower(&mut self) -> Option<Hir> {
+        let mut functions: Vec<Function> = vec![];
+        for stmt in self.ast.body.clone() {
+            self.lower_item(&stmt, &mut functions)?;
+        }
+        Some(Hir { functions, definitions: self.definitions.clone(), structs: self.structs.clone(), enums: self.enums.clone(), layouts: self.layouts.clone() })
+    }
+    // Reports a broken invariant of a checked program, lowering gives up on it
+    fn lowering_failed<T>(&self, span: Span, message: &str) -> Option<T> {
+        self.diag.print_formatted_at(DiagType::Ice, span, message.to_string());
+        None
+    }
+    fn lower_item(&mut self, stmt: &StatementType, functions: &mut Vec<Function>) -> Option<()> {
+        match stmt {
+            StatementType::Decleration(DeclerationType::Function(func)) => {
+                functions.push(self.lower_func(func)?);
+            }
+            StatementType::Decleration(DeclerationType::Namespace(namespace)) => {
+                let Some(SymbolKind::Namespace(scope)) = self.get_local(self.current_scope, &namespace.name().get_data()) else {
+                    return self.lowering_failed(namespace.span(), "Namespace without a scope");
+                };
+                self.enter_scope(scope);
+                for stmt in namespace.body() {
+                    self.lower_item(&stmt, functions)?;
+                }
+                self.leave_scope();
+            }
+            // Types are in the definitions checking collected
+            _ => {}
+        }
+        Some(())
+    }
+    fn lower_func(&mut self, func: &FunctionDeclerationStatement) -> Option<Function> {
+        let scope: Option<usize> = self.node_scopes.get(&func.get_id()).copied();
+        let Some((scope, definition)) = scope.and_then(|scope| Some((scope, self.scopes[scope].owner?))) else {
+            return self.lowering_failed(func.span(), "Function without a scope");
+        };
+        let SymbolKind::Function(_, return_type) = self.definitions[definition].kind.clone() else {
+            return self.lowering_failed(func.span(), "Function scope owned by something other than a function");
+        };
+        let params: Vec<DefinitionId> = func.params().iter().filter_map(|param| self.local(scope, &param.name.get_data())).collect();
+        self.enter_scope(scope);
+        let enclosing: Option<(String, TypeSpec)> = self.current_function.replace((func.name().get_data(), return_type.clone()));
+        let body: Option<Stmt> = match func.body() {
+            Some(body) => Some(self.lower_stmt(&body)?),
+            None => None,
+        };
+        self.current_function = enclosing;
+        self.leave_scope();
+        Some(Function { name: self.scopes[scope].path.clone(), definition, params, return_type, body, span: func.span() })
+    }
+    fn lower_stmt(&mut self, stmt: &StatementType) -> Option<Stmt> {
+        let lowered: Stmt = match stmt {
+            StatementType::Block(block) => {
+                self.enter_node_scope(block.get_id());
+                let body: Vec<Stmt> = block.body.iter().map(|stmt| self.lower_stmt(stmt)).collect::<Option<_>>()?;
+                self.leave_scope();
+                Stmt::Block(body, block.span())
+            }
+            StatementType::Decleration(DeclerationType::Variable(var)) => {
+                let Some(definition) = self.local(self.current_scope, &var.name().get_data()) else {
+                    return self.lowering_failed(var.span(), "Undeclared variable");
+                };
+                let SymbolKind::Variable(type_spec) = self.definitions[definition].kind.clone() else {
+                    return self.lowering_failed(var.span(), "`var` declared something other than a variable");
+                };
+                let initializer: Option<Expr> = match var.initializer() {
+                    Some(initializer) => Some(self.lower_expr_as(&initializer, &type_spec)?),
+                    None => None,
+                };
+                Stmt::Let(definition, initializer, var.span())
+            }
+            StatementType::Expr(expr, span) => Stmt::Expr(self.lower_expr(expr)?, *span),
+            StatementType::Return(expr, span) => {
+                let return_type: TypeSpec = self.current_function.as_ref().map_or(TypeSpec::Invalid, |(_, return_type)| return_type.clone());
+                let value: Option<Expr> = match expr {
+                    Some(expr) => Some(self.lower_expr_as(expr, &return_type)?),
+                    None => None,
+                };
+                Stmt::Return(value, *span)
+            }
+            StatementType::If(if_stmt) => {
+                let condition: Expr = self.lower_condition(&if_stmt.condition())?;
+                let then_branch: Stmt = self.lower_stmt(&if_stmt.then_branch())?;
+                let else_branch: Option<Box<Stmt>> = match if_stmt.else_branch() {
+                    Some(else_branch) => Some(Box::new(self.lower_stmt(&else_branch)?)),
+                    None => None,
+                };
+                Stmt::If(condition, Box::new(then_branch), else_branch, if_stmt.span())
+            }
+            StatementType::While(while_stmt) => {
+                let condition: Expr = self.lower_condition(&while_stmt.condition())?;
+                Stmt::While(condition, Box::new(self.lower_stmt(&while_stmt.body())?), while_stmt.span())
+            }
+            StatementType::For(for_stmt) => {
+                let iterable: Expr = self.lower_expr(&for_stmt.iterable())?;
+                let scope: Option<usize> = self.node_scopes.get(&for_stmt.get_id()).copied();
+                let Some((scope, variable)) = scope.and_then(|scope| Some((scope, self.local(scope, &for_stmt.variable().get_data())?))) else {
+                    return self.lowering_failed(for_stmt.span(), "Loop variable isn't declared in the loop's scope");
+                };
+                self.enter_scope(scope);
+                let body: Stmt = self.lower_stmt(&for_stmt.body())?;
+                self.leave_scope();
+                Stmt::For(variable, iterable, Box::new(body), for_stmt.span())
+            }
+            StatementType::Decleration(DeclerationType::Function(_) | DeclerationType::Namespace(_) | DeclerationType::Struct(_) | DeclerationType::TypeAlias(_) | DeclerationType::Enum(_)) => {
+                return self.lowering_failed(stmt.span(), "Only variables are declared in function bodies");
+            }
+            StatementType::Decleration(DeclerationType::Invalid(_)) | StatementType::Invalid(_) => {
+                return self.lowering_failed(stmt.span(), "Invalid statement in a program without errors");
+            }
+        };
+        Some(lowered)
+    }
+    // Conditions are `bool` or an integer, which is compared with `0`
+    fn lower_condition(&mut self, condition: &ExprType) -> Option<Expr> {
+        let condition: Expr = self.lower_expr(condition)?;
+        if condition.type_spec == TypeSpec::Bool {
+            return Some(condition);
+        }
+        let span: Span = condition.span;
+        Some(Expr { kind: ExprKind::Convert(Box::new(condition)), type_spec: TypeSpec::Bool, span })
+    }
+    fn lower_expr(&mut self, expr: &ExprType) -> Option<Expr> {
+        let type_spec: TypeSpec = self.evaluate_expr_to_type(expr.clone());
+        self.lower_typed(expr, type_spec)
+    }
+    // `expr` where a value of type `expected` is wanted, unsuffixed literals take that type
+    // and anything that converts to it implicitly is converted
+    fn lower_expr_as(&mut self, expr: &ExprType, expected: &TypeSpec) -> Option<Expr> {
+        let type_spec: TypeSpec = self.evaluate_expr_as(expr.clone(), expected);
+        let lowered: Expr = self.lower_typed(expr, type_spec)?;
+        Some(Self::convert(lowered, expected))
+    }
+    // Wraps `expr` in a `Convert` to `to` if its type is a different one that converts implicitly
+    fn convert(expr: Expr, to: &TypeSpec) -> Expr {
+        if expr.type_spec == *to || *to == TypeSpec::Invalid || !is_assignable(&expr.type_spec, to) {
+            return expr;
+        }
+        let span: Span = expr.span;
+        Expr { kind: ExprKind::Convert(Box::new(expr)), type_spec: to.clone(), span }
+    }
+    // The definition a name or the last segment of a `namespace::name` path was resolved to
+    fn definition_of(&self, path: &ExprType) -> Option<DefinitionId> {
+        let name: &ExprType = match path {
+            ExprType::MemberAccess(_, property, _) => property,
+            _ => path,
+        };
+        let id: Option<usize> = match name {
+            ExprType::Identifier(_, id, _) => Some(*id),
+            _ => None,
+        };
+        match id.and_then(|id| self.resolutions.get(&id)) {
+            Some(&definition) => Some(definition),
+            None => self.lowering_failed(path.span(), "Unresolved name"),
+        }
+    }
+    // `expr` as a node of type `type_spec`, which was evaluated for it
+    fn lower_typed(&mut self, expr: &ExprType, type_spec: TypeSpec) -> Option<Expr> {
+        let span: Span = expr.span();
+        let kind: ExprKind = match expr {
+            ExprType::NumericLiteral(literal, _) => {
+                let text: String = literal.get_data();
+                ExprKind::Integer(integer_literal_value(&text).0.and_then(|value| i128::try_from(value).ok()).unwrap_or_default())
+            }
+            ExprType::FloatLiteral(literal, _) => {
+                let text: String = literal.get_data();
+                ExprKind::Float(float_literal_value(&text).0.unwrap_or_default())
+            }
+            ExprType::StringLiteral(literal, _) => ExprKind::String(literal.get_data()),
+            ExprType::CharLiteral(literal, _) => ExprKind::Char(literal.get_data().chars().next().unwrap_or('\0')),
+            ExprType::BoolLiteral(literal, _) => ExprKind::Bool(literal.get_type() == TokenType::True),
+            ExprType::NullLiteral(_, _) => ExprKind::Null,
+            ExprType::Identifier(..) => ExprKind::Variable(self.definition_of(expr)?),
+            ExprType::MemberAccess(..) => match self.resolve_path(expr) {
+                Some(SymbolKind::Variant(key, index)) => ExprKind::Variant(key, index, vec![]),
+                _ => ExprKind::Variable(self.definition_of(expr)?),
+            },
+            ExprType::Unary(op, operand, _) => {
+                let operand: Expr = match op.get_type() {
+                    TokenType::Bang => self.lower_condition(operand)?,
+                    // A negated literal has the type of the whole, `-128` can be an `i8` though `128` can't
+                    TokenType::Minus if Self::is_unsuffixed_literal(operand) => self.lower_typed(operand, type_spec.clone())?,
+                    _ => self.lower_expr(operand)?,
+                };
+                ExprKind::Unary(op.get_type(), Box::new(operand))
+            }
+            ExprType::Binary(left, op, right, _) => {
+                let (left, right): (Expr, Expr) = self.lower_operands(left, op.get_type(), right)?;
+                ExprKind::Binary(Box::new(left), op.get_type(), Box::new(right))
+            }
+            ExprType::Assign(target, op, value, _) => {
+                let target: Expr = self.lower_expr(target)?;
+                let value: Expr = self.lower_expr_as(value, &target.type_spec)?;
+                // The type of an assignment is the target's, whether or not it's a variable
+                return Some(Expr { type_spec: target.type_spec.clone(), kind: ExprKind::Assign(Box::new(target), op.get_type(), Box::new(value)), span });
+            }
+            ExprType::FieldAccess(base, field, _) => {
+                let base: Expr = self.lower_expr(base)?;
+                let index: usize = self.field_index(&base.type_spec, &field.get_data(), span)?;
+                ExprKind::Field(Box::new(base), index)
+            }
+            ExprType::StructLiteral(_, fields, _) => {
+                let key: String = Self::struct_key(&type_spec);
+                let mut values: Vec<(usize, Expr)> = vec![];
+                for field in fields {
+                    let index: usize = self.field_index(&type_spec, &field.name.get_data(), field.span)?;
+                    let field_type: TypeSpec = self.structs[&key].fields[index].type_spec.clone();
+                    values.push((index, self.lower_expr_as(&field.value, &field_type)?));
+                }
+                ExprKind::StructLiteral(values)
+            }
+            ExprType::Call(callee, args, _) => {
+                let (param_types, variant): (Vec<TypeSpec>, Option<(String, usize)>) = match self.resolve_path(callee) {
+                    Some(SymbolKind::Variant(key, index)) => (self.enums[&key].variants[index].fields.clone(), Some((key, index))),
+                    Some(SymbolKind::Function(param_types, _)) => (param_types, None),
+                    _ => return self.lowering_failed(callee.span(), "Call of something other than a function or a variant"),
+                };
+                let args: Vec<Expr> = args.iter().zip(&param_types).map(|(arg, param_type)| self.lower_expr_as(arg, param_type)).collect::<Option<_>>()?;
+                match variant {
+                    Some((key, index)) => ExprKind::Variant(key, index, args),
+                    None => ExprKind::Call(self.definition_of(callee)?, args),
+                }
+            }
+            ExprType::Cast(value, _, _) => ExprKind::Cast(Box::new(self.lower_expr(value)?)),
+            ExprType::Match(match_expr) => self.lower_match(match_expr, &type_spec)?,
+            ExprType::Invalid(_) => return self.lowering_failed(span, "Invalid expression in a program without errors"),
+        };
+        Some(Expr { kind, type_spec, span })
+    }
+    // Operands of a binary operator, an unsuffixed literal takes the type of the other operand like in
+    // `evaluate_expr_to_type` and the narrower operand is converted to the type of the wider one
+    fn lower_operands(&mut self, left: &ExprType, op: TokenType, right: &ExprType) -> Option<(Expr, Expr)> {
+        if matches!(op, TokenType::AmpersandAmpersand | TokenType::PipePipe) {
+            return Some((self.lower_condition(left)?, self.lower_condition(right)?));
+        }
+        let shift: bool = matches!(op, TokenType::LessLess | TokenType::GreaterGreater);
+        let (left, right): (Expr, Expr) = match (Self::is_unsuffixed_literal(left), Self::is_unsuffixed_literal(right)) {
+            (true, false) if !shift => {
+                let right: Expr = self.lower_expr(right)?;
+                (self.lower_expr_as(left, &right.type_spec)?, right)
+            }
+            (false, true) if !shift => {
+                let left: Expr = self.lower_expr(left)?;
+                let right: Expr = self.lower_expr_as(right, &left.type_spec)?;
+                (left, right)
+            }
+            _ => (self.lower_expr(left)?, self.lower_expr(right)?),
+        };
+        if shift {
+            return Some((left, right));
+        }
+        let (left_type, right_type): (TypeSpec, TypeSpec) = (left.type_spec.clone(), right.type_spec.clone());
+        if is_assignable(&left_type, &right_type) {
+            Some((Self::convert(left, &right_type), right))
+        } else {
+            Some((left, Self::convert(right, &left_type)))
+        }
+    }
+    fn field_index(&self, struct_type: &TypeSpec, field: &str, span: Span) -> Option<usize> {
+        let index: Option<usize> = self.structs.get(&Self::struct_key(struct_type)).and_then(|definition| definition.fields.iter().position(|candidate| candidate.name.get_data() == field));
+        match index {
+            Some(index) => Some(index),
+            None => self.lowering_failed(span, "Access of a field that doesn't exist"),
+        }
+    }
+    // The arms of a `match` of type `match_type`, expression arms produce a value of that type
+    fn lower_match(&mut self, match_expr: &MatchExpression, match_type: &TypeSpec) -> Option<ExprKind> {
+        let scrutinee: Expr = self.lower_expr(&match_expr.scrutinee())?;
+        let mut arms: Vec<Arm> = vec![];
+        for arm in match_expr.arms() {
+            self.enter_node_scope(arm.get_id());
+            let pattern: Pattern = self.lower_pattern(&arm.pattern(), &scrutinee.type_spec)?;
+            let body: Stmt = match arm.body() {
+                StatementType::Expr(expr, span) if *match_type != TypeSpec::Void => Stmt::Expr(self.lower_expr_as(&expr, match_type)?, span),
+                body => self.lower_stmt(&body)?,
+            };
+            self.leave_scope();
+            arms.push(Arm { pattern, body, span: arm.span() });
+        }
+        Some(ExprKind::Match(Box::new(scrutinee), arms))
+    }
+    fn lower_pattern(&mut self, pattern: &AstPattern, expected: &TypeSpec) -> Option<Pattern> {
+        let lowered: Pattern = match pattern {
+            AstPattern::Wildcard(_) => Pattern::Wildcard,
+            AstPattern::Binding(name, span) => match self.local(self.current_scope, &name.get_data()) {
+                Some(binding) => Pattern::Binding(binding),
+                None => return self.lowering_failed(*span, "Binding isn't declared in the arm's scope"),
+            },
+            AstPattern::Literal(literal, _) => Pattern::Literal(self.lower_expr_as(literal, expected)?),
+            AstPattern::Variant(path, fields, span) => {
+                let Some(SymbolKind::Variant(key, index)) = self.resolve_path(path) else {
+                    return self.lowering_failed(*span, "Variant pattern that doesn't name a variant");
+                };
+                let field_types: Vec<TypeSpec> = self.enums[&key].variants[index].fields.clone();
+                let fields: Vec<Pattern> = fields.iter().zip(&field_types).map(|(field, field_type)| self.lower_pattern(field, field_type)).collect::<Option<_>>()?;
+                Pattern::Variant(key, index, fields)
+            }
+        };
+        Some(lowered)
+    }
+}
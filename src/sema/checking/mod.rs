@@ -1 +1,6 @@
-pub mod sema;
\ No newline at end of file
+pub mod sema;
+pub mod layout;
+pub mod types;
+pub mod patterns;
+pub mod flow;
+pub mod lower;
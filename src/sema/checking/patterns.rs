@@ -0,0 +1,94 @@
+use crate::syntax::ast::TypeSpec;
+
+// A `match` pattern reduced to what exhaustiveness checking needs, bindings are wildcards
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pat {
+    Wild,
+    Constructor(Constructor, Vec<Pat>), // With a pattern for each field of the constructor
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Constructor {
+    Variant(usize), // Index of the variant in its enum
+    Bool(bool),
+    Literal(String), // Any other literal, by its value
+}
+
+// Every constructor of a type and the types of its fields, `None` for types with too many
+// values to list, like `int`, which only a wildcard covers completely
+pub type Constructors<'a> = &'a dyn Fn(&TypeSpec) -> Option<Vec<(Constructor, Vec<TypeSpec>)>>;
+
+// Rows of `matrix` whose first pattern matches `constructor`, with that pattern replaced by its fields
+fn specialize(matrix: &[Vec<Pat>], constructor: &Constructor, arity: usize) -> Vec<Vec<Pat>> {
+    matrix.iter().filter_map(|row| {
+        let (first, rest) = row.split_first()?;
+        let mut fields: Vec<Pat> = match first {
+            Pat::Wild => vec![Pat::Wild; arity],
+            Pat::Constructor(candidate, fields) if candidate == constructor => fields.clone(),
+            Pat::Constructor(..) => return None,
+        };
+        fields.extend_from_slice(rest);
+        Some(fields)
+    }).collect()
+}
+
+// Rows of `matrix` that start with a wildcard, without it
+fn default_rows(matrix: &[Vec<Pat>]) -> Vec<Vec<Pat>> {
+    matrix.iter().filter(|row| row.first() == Some(&Pat::Wild)).map(|row| row[1..].to_vec()).collect()
+}
+
+// Folds the first `arity` patterns of `values` back into the fields of `constructor`
+fn rebuild(constructor: Constructor, arity: usize, mut values: Vec<Pat>) -> Vec<Pat> {
+    let rest: Vec<Pat> = values.split_off(arity);
+    let mut row: Vec<Pat> = vec![Pat::Constructor(constructor, values)];
+    row.extend(rest);
+    row
+}
+
+// Values matched by `row` but by no row of `matrix`, one pattern per column of `types`, or `None`
+// if every value `row` matches is already matched. This is the usefulness check from Maranget's
+// "Warnings for pattern matching", extended to build the values it finds
+pub fn witness(matrix: &[Vec<Pat>], row: &[Pat], types: &[TypeSpec], constructors: Constructors) -> Option<Vec<Pat>> {
+    let Some((first, rest)) = row.split_first() else {
+        return matrix.is_empty().then(Vec::new);
+    };
+    let all: Option<Vec<(Constructor, Vec<TypeSpec>)>> = constructors(&types[0]);
+    // Specializes by `constructor`, looking for values that start with it
+    let with_constructor = |constructor: Constructor, fields: Vec<Pat>, field_types: Vec<TypeSpec>| {
+        let arity: usize = fields.len();
+        let specialized_row: Vec<Pat> = fields.into_iter().chain(rest.iter().cloned()).collect();
+        let specialized_types: Vec<TypeSpec> = field_types.into_iter().chain(types[1..].iter().cloned()).collect();
+        let values: Vec<Pat> = witness(&specialize(matrix, &constructor, arity), &specialized_row, &specialized_types, constructors)?;
+        Some(rebuild(constructor, arity, values))
+    };
+    match first {
+        Pat::Constructor(constructor, fields) => {
+            let field_types: Vec<TypeSpec> = all.and_then(|all| all.into_iter().find(|(candidate, _)| candidate == constructor))
+                .map_or_else(|| vec![TypeSpec::Invalid; fields.len()], |(_, field_types)| field_types);
+            with_constructor(constructor.clone(), fields.clone(), field_types)
+        }
+        Pat::Wild => {
+            let used: Vec<&Constructor> = matrix.iter().filter_map(|row| match row.first() {
+                Some(Pat::Constructor(constructor, _)) => Some(constructor),
+                _ => None,
+            }).collect();
+            match all {
+                // Every constructor appears in the first column, so the wildcard is only useful
+                // if it is for one of them
+                Some(all) if all.iter().all(|(constructor, _)| used.contains(&constructor)) => {
+                    all.into_iter().find_map(|(constructor, field_types)| with_constructor(constructor, vec![Pat::Wild; field_types.len()], field_types))
+                }
+                all => {
+                    let mut values: Vec<Pat> = witness(&default_rows(matrix), rest, &types[1..], constructors)?;
+                    // Naming a constructor that no row starts with reads better than `_`
+                    let missing: Option<(Constructor, Vec<TypeSpec>)> = all.and_then(|all| all.into_iter().find(|(constructor, _)| !used.contains(&constructor)));
+                    values.insert(0, match missing {
+                        Some((constructor, field_types)) => Pat::Constructor(constructor, vec![Pat::Wild; field_types.len()]),
+                        None => Pat::Wild,
+                    });
+                    Some(values)
+                }
+            }
+        }
+    }
+}
@@ -1,221 +1,1672 @@
-use crate::{driver::diag::{DiagPrinter, DiagType}, syntax::ast::{Ast, DeclerationType, ExprType, FunctionDeclerationStatement, StatementType, TypeSpec}};
-use std::collections::HashMap;
-use std::process::exit;
+use crate::{driver::diag::{DiagPrinter, DiagType, Diagnostic}, syntax::{ast::{Ast, DeclerationType, EnumDeclerationStatement, ExprType, Field, FieldInitializer, ForStatement, FunctionDeclerationStatement, MatchExpression, NamespaceDeclerationStatement, Pattern, StatementType, StructDeclerationStatement, TypeAliasDeclerationStatement, TypeSpec, Variant, VariableDeclerationStatement}, lexer::{float_literal_value, integer_literal_value}, span::Span, token::{Token, TokenType}}};
+use super::{flow::ControlFlowGraph, layout::{builtin_layout, Layout}, patterns::{witness, Constructor, Pat}, types::{binary_type, cast_hint, conversion_hint, instance_depth, integer_range, is_assignable, is_condition_type, is_float, is_integer, is_numeric, is_valid_cast, substitute, INTEGER_TYPES}};
+use std::collections::{HashMap, HashSet};
+
+// Instances of a generic struct nested deeper than this are reported instead of being
+// instantiated, `struct W<T> { inner: W<W<T>>* }` would otherwise never stop
+const MAX_INSTANCE_DEPTH: usize = 32;
+
+// Type of the values a `for` loop produces when iterating over `type_spec`,
+// only `std::Array` can be iterated over
+fn element_type(type_spec: &TypeSpec) -> Option<TypeSpec> {
+    match type_spec.canonical() {
+        TypeSpec::Instance(key, args) if key == "std::Array" => args.first().cloned(),
+        _ => None,
+    }
+}
 
 pub struct SemaChecker {
-    ast: Ast,
-    diag: DiagPrinter,
-    pub scopes: HashMap<String, SymbolTable>,
-    pub current_scope: String,
-    pub scope_names: Vec<String>,
+    pub(super) ast: Ast,
+    pub(super) diag: DiagPrinter,
+    pub scopes: Vec<SymbolTable>, // Indexed by `ScopeId`, the top level scope is `TOP_SCOPE`
+    pub definitions: Vec<Definition>, // Indexed by `DefinitionId`
+    pub resolutions: HashMap<usize, DefinitionId>, // What each name used in an expression refers to, by the id of the `Identifier`
+    pub(super) node_scopes: HashMap<usize, ScopeId>, // Scope of each function, block, `for` loop and `match` arm by its id
+    pub current_scope: ScopeId,
+    pub scope_stack: Vec<ScopeId>,
+    pub structs: HashMap<String, StructDefinition>, // Keyed by the name qualified with the namespaces it's declared in
+    pub layouts: HashMap<String, Option<Layout>>, // `None` for structs and enums that can't be laid out
+    pub aliases: HashMap<String, AliasDefinition>, // Keyed like `structs`
+    pub enums: HashMap<String, EnumDefinition>, // Keyed like `structs`
+    type_params: Vec<String>, // Of the generic struct whose fields are being resolved
+    match_types: HashMap<usize, TypeSpec>, // Type of each `match` by its id, set when it's validated
+    non_exhaustive_matches: HashSet<usize>, // Ids of the `match`es reported as non-exhaustive, they can fall through
+    pub(super) current_function: Option<(String, TypeSpec)>, // Name and return type of the function being validated
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructDefinition {
+    pub name: Token,
+    pub type_params: Vec<String>, // Empty for plain structs and instances of generic ones
+    pub fields: Vec<Field>, // With their types resolved, type parameters are left as `TypeSpec::Parameter`
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumDefinition {
+    pub name: Token,
+    pub variants: Vec<Variant>, // With their field types resolved
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AliasDefinition {
+    pub name: Token,
+    pub type_spec: TypeSpec, // As written
+    pub scope: ScopeId, // Where it was declared, which is where `type_spec` is resolved
+    pub span: Span,
+    pub resolved: Option<TypeSpec>, // Set by `resolve_aliases`, `Invalid` for aliases that refer to themselves
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum SymbolKind {
     Variable(TypeSpec),
     Function(Vec<TypeSpec>, TypeSpec), // Arguments, Return type
-    Namespace(*mut SymbolTable), // Recursive symbols
+    Namespace(ScopeId), // The namespace's own scope
+    Struct(String), // Key of the definition in `SemaChecker::structs`
+    TypeAlias(String), // Key of the definition in `SemaChecker::aliases`
+    Enum(String), // Key of the definition in `SemaChecker::enums`
+    Variant(String, usize), // Key of the enum and the variant's index, only named through `Enum::Variant` paths
+}
+
+pub type ScopeId = usize;
+pub type DefinitionId = usize;
+
+pub const TOP_SCOPE: ScopeId = 0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScopeKind {
+    Top,
+    Namespace,
+    Function, // Holds the parameters
+    Block, // Blocks, `for` loops and `match` arms
 }
 
-#[derive(Debug, Clone, Default, PartialEq)]
+// A scope in the tree of scopes, its names map to their definitions in `SemaChecker::definitions`
+#[derive(Debug, Clone, PartialEq)]
 pub struct SymbolTable {
-    symbols: HashMap<String, SymbolKind>,
-    parent: Option<String>
+    pub kind: ScopeKind,
+    pub parent: Option<ScopeId>,
+    pub path: String, // Qualifies the keys of types declared in it, `a::b` for namespace `b` in `a`
+    pub owner: Option<DefinitionId>, // The function a function scope belongs to
+    symbols: HashMap<String, DefinitionId>,
 }
 
-impl SymbolTable {
-    pub fn new(parent: Option<String>) -> Self {
-        Self { symbols: HashMap::new(), parent }
-    }
-    pub fn get_symbol_by_name(&self, semacheck: &SemaChecker, name: &String) -> Option<SymbolKind> {
-        if let Some(symbol) = self.symbols.get(name) {
-            Some(symbol.clone())
-        } else if let Some(ref parent) = self.parent {
-            // Recursively look in the parent scope
-            if let Some(parent_scope) = semacheck.get_scope(parent) {
-                parent_scope.get_symbol_by_name(semacheck, name)
-            } else {
-                None
-            }
-        } else {
-            None
-        }
-    }
-    pub fn contains(&self, semacheck: &SemaChecker, name: &String) -> bool {
-        self.symbols.contains_key(name) || 
-        self.parent.as_ref().and_then(|parent| {
-            if let Some(parent_scope) = semacheck.get_scope(parent) {
-                Some(parent_scope.contains(semacheck, name))
-            } else {
-                None
-            }
-        }).unwrap_or(false)
-    }
-    pub fn append(&mut self, name: String, kind: SymbolKind) {
-        self.symbols.insert(name, kind);
-    }
+#[derive(Debug, Clone, PartialEq)]
+pub struct Definition {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub location: Option<Span>, // `None` for builtins
+    pub scope: ScopeId,
+    pub visible_from: usize, // Offset the name can be used from, the end of the whole statement for a `var`
 }
 
 impl SemaChecker {
     pub fn new(ast: Ast, diag: DiagPrinter) -> Self {
-        let table: SymbolTable = SymbolTable::new(None);
-        let mut hash: HashMap<String, SymbolTable> = HashMap::new();
-        hash.insert("__top_scope__".to_string(), table);
-        Self { ast, diag, scopes: hash, current_scope: "__top_scope__".to_string(), scope_names: vec![]}
+        let top: SymbolTable = SymbolTable { kind: ScopeKind::Top, parent: None, path: String::new(), owner: None, symbols: HashMap::new() };
+        Self { ast, diag, scopes: vec![top], definitions: vec![], resolutions: HashMap::new(), node_scopes: HashMap::new(), current_scope: TOP_SCOPE, scope_stack: vec![], structs: HashMap::new(), layouts: HashMap::new(), aliases: HashMap::new(), enums: HashMap::new(), type_params: vec![], match_types: HashMap::new(), non_exhaustive_matches: HashSet::new(), current_function: None }
     }
     fn init(&mut self) {
-        let mut std_table: SymbolTable = SymbolTable::new(Some("__top_scope__".to_string()));
-        std_table.append("println".to_string(), SymbolKind::Function(vec![TypeSpec::String], TypeSpec::Int));
-        self.add_table("std".to_string(), std_table.clone());
-        self.append_top("std".to_string(), SymbolKind::Namespace(&mut std_table));
+        let std_scope: ScopeId = self.add_scope(ScopeKind::Namespace, "std".to_string());
+        self.define(std_scope, "println".to_string(), SymbolKind::Function(vec![TypeSpec::String], TypeSpec::Int), None);
+        // `struct Array<T> { data: T*, length: int }`
+        let field = |name: &str, type_spec: TypeSpec| Field { name: Token::new(TokenType::Identifier, name.to_string(), Span::default()), type_spec, span: Span::default() };
+        let element: TypeSpec = TypeSpec::Parameter("T".to_string());
+        self.structs.insert("std::Array".to_string(), StructDefinition {
+            name: Token::new(TokenType::Identifier, "Array".to_string(), Span::default()),
+            type_params: vec!["T".to_string()],
+            fields: vec![field("data", TypeSpec::Pointer(Box::new(element))), field("length", TypeSpec::Int)],
+        });
+        self.define(std_scope, "Array".to_string(), SymbolKind::Struct("std::Array".to_string()), None);
+        // `type string = string;`, so the builtin can also be spelled `std::string`
+        self.aliases.insert("std::string".to_string(), AliasDefinition {
+            name: Token::new(TokenType::Identifier, "string".to_string(), Span::default()),
+            type_spec: TypeSpec::String,
+            scope: std_scope,
+            span: Span::default(),
+            resolved: Some(TypeSpec::String),
+        });
+        self.define(std_scope, "string".to_string(), SymbolKind::TypeAlias("std::string".to_string()), None);
+        self.define(TOP_SCOPE, "std".to_string(), SymbolKind::Namespace(std_scope), None);
+    }
+    // Adds a scope nested in the current one
+    fn add_scope(&mut self, kind: ScopeKind, path: String) -> ScopeId {
+        self.scopes.push(SymbolTable { kind, parent: Some(self.current_scope), path, owner: None, symbols: HashMap::new() });
+        self.scopes.len() - 1
+    }
+    fn define(&mut self, scope: ScopeId, name: String, kind: SymbolKind, location: Option<Span>) -> DefinitionId {
+        let id: DefinitionId = self.definitions.len();
+        self.definitions.push(Definition { name: name.clone(), kind, location, scope, visible_from: location.map_or(0, |location| location.start) });
+        // A redefinition was reported by the caller, the name keeps referring to the first definition
+        self.scopes[scope].symbols.entry(name).or_insert(id);
+        id
+    }
+    fn define_current(&mut self, name: String, kind: SymbolKind, location: Option<Span>) -> DefinitionId {
+        self.define(self.current_scope, name, kind, location)
     }
-    pub fn get_scope(&self, name: &str) -> Option<SymbolTable> {
-        self.scopes.get(name).cloned()
+    // Only looks in `scope` itself, used for `namespace::name` where parents don't apply
+    pub fn local(&self, scope: ScopeId, name: &str) -> Option<DefinitionId> {
+        self.scopes[scope].symbols.get(name).copied()
+    }
+    pub(super) fn get_local(&self, scope: ScopeId, name: &str) -> Option<SymbolKind> {
+        self.local(scope, name).map(|id| self.definitions[id].kind.clone())
+    }
+    fn local_location(&self, scope: ScopeId, name: &str) -> Option<Span> {
+        self.local(scope, name).and_then(|id| self.definitions[id].location)
+    }
+    // Looks in `scope` and then in each of its parents
+    pub fn lookup(&self, scope: ScopeId, name: &str) -> Option<DefinitionId> {
+        let mut current: Option<ScopeId> = Some(scope);
+        while let Some(scope) = current {
+            if let Some(id) = self.local(scope, name) {
+                return Some(id);
+            }
+            current = self.scopes[scope].parent;
+        }
+        None
     }
-    fn get_symbol(&self, name: String) -> Option<SymbolKind> {
-        if let Some(sym_table) = self.scopes.get(&name) {
-            return sym_table.get_symbol_by_name(self, &name);
+    // Like `lookup` from the current scope, but variables that aren't visible at `position` yet are skipped
+    // and the search continues with the parents, where a variable it shadows may already be visible
+    fn lookup_visible_definition(&self, name: &str, position: usize) -> Option<DefinitionId> {
+        let mut current: Option<ScopeId> = Some(self.current_scope);
+        while let Some(scope) = current {
+            if let Some(id) = self.local(scope, name) {
+                let definition: &Definition = &self.definitions[id];
+                let declared_later: bool = matches!(definition.kind, SymbolKind::Variable(_)) && definition.visible_from > position;
+                if !declared_later {
+                    return Some(id);
+                }
+            }
+            current = self.scopes[scope].parent;
         }
         None
     }
-    fn add_table(&mut self, name: String, table: SymbolTable) {
-        self.scopes.insert(name, table);
+    fn lookup_visible(&self, name: &str, position: usize) -> Option<SymbolKind> {
+        self.lookup_visible_definition(name, position).map(|id| self.definitions[id].kind.clone())
     }
-    fn append_top(&mut self, name: String, kind: SymbolKind) {
-        let table: String = "__top_scope__".to_string();
-        if let Some(sym_table) = self.scopes.get_mut(&table) { // Get a mutable reference
-            sym_table.append(name, kind);
-        } else {
-            eprintln!("Error: '__top_scope__' does not exist in scopes!");
+    // Name for a type, function or namespace declared in the current scope,
+    // qualified so equally named declarations in different namespaces don't collide
+    fn scoped_name(&self, name: &str) -> String {
+        match self.scopes[self.current_scope].path.as_str() {
+            "" => name.to_string(),
+            path => format!("{}::{}", path, name),
         }
     }
-    fn contains_name(&self, table: String, name: String) -> bool {
-        if let Some(sym_table) = self.scopes.get(&table) {
-            sym_table.contains(self, &name)
-        } else {
-            false
+    // Symbol named by a `namespace::name` path written in a type annotation
+    fn lookup_path(&self, path: &str) -> Option<SymbolKind> {
+        let mut segments = path.split("::");
+        let mut symbol: SymbolKind = self.definitions[self.lookup(self.current_scope, segments.next()?)?].kind.clone();
+        for segment in segments {
+            let SymbolKind::Namespace(scope) = symbol else {
+                return None;
+            };
+            symbol = self.get_local(scope, segment)?;
         }
+        Some(symbol)
+    }
+    // Replaces names in a type annotation with what they refer to, `span` is where the annotation was written
+    fn resolve_type(&self, type_spec: &TypeSpec, span: Span) -> TypeSpec {
+        if *type_spec == TypeSpec::Void {
+            self.diag.print_formatted_at(DiagType::Error, span, "`void` is only valid as a return type".to_string());
+            return TypeSpec::Invalid;
+        }
+        if let TypeSpec::Pointer(pointee) = type_spec {
+            return match self.resolve_type(pointee, span) {
+                TypeSpec::Invalid => TypeSpec::Invalid,
+                pointee => TypeSpec::Pointer(Box::new(pointee)),
+            };
+        }
+        let (path, args): (&String, &[TypeSpec]) = match type_spec {
+            TypeSpec::Named(path) if self.type_params.contains(path) => return TypeSpec::Parameter(path.clone()),
+            TypeSpec::Named(path) => (path, &[]),
+            TypeSpec::Generic(path, args) => (path, args),
+            _ => return type_spec.clone(),
+        };
+        match self.lookup_path(path) {
+            Some(SymbolKind::Struct(key)) => self.resolve_struct_type(key, path, args, span),
+            Some(SymbolKind::Enum(_)) if !args.is_empty() => {
+                self.diag.print_formatted_at(DiagType::Error, span, format!("Enum `{}` isn't generic and takes no type arguments", path));
+                TypeSpec::Invalid
+            }
+            Some(SymbolKind::Enum(key)) => TypeSpec::Enum(key),
+            Some(SymbolKind::TypeAlias(_)) if !args.is_empty() => {
+                self.diag.print_formatted_at(DiagType::Error, span, format!("Type alias `{}` isn't generic and takes no type arguments", path));
+                TypeSpec::Invalid
+            }
+            // Aliases are resolved before anything that can name them
+            Some(SymbolKind::TypeAlias(key)) => match self.aliases.get(&key).and_then(|alias| alias.resolved.clone()) {
+                Some(TypeSpec::Invalid) | None => TypeSpec::Invalid,
+                Some(aliased) => TypeSpec::Alias(path.clone(), Box::new(aliased)),
+            },
+            Some(_) => {
+                self.diag.print_formatted_at(DiagType::Error, span, format!("`{}` is not a type", path));
+                TypeSpec::Invalid
+            }
+            None => {
+                self.diag.print_formatted_at(DiagType::Error, span, format!("Unknown type `{}`", path));
+                TypeSpec::Invalid
+            }
+        }
+    }
+    // A struct named in a type annotation, generic ones need exactly as many arguments as they
+    // have parameters and become an instance that `instantiate_generics` gives its own definition
+    fn resolve_struct_type(&self, key: String, path: &str, args: &[TypeSpec], span: Span) -> TypeSpec {
+        let expected: usize = self.structs.get(&key).map_or(0, |definition| definition.type_params.len());
+        if args.len() != expected {
+            let message: String = match expected {
+                0 => format!("Struct `{}` isn't generic and takes no type arguments", path),
+                _ => format!("Struct `{}` expects {} type argument{} but {} {} given", path, expected, if expected == 1 { "" } else { "s" }, args.len(), if args.len() == 1 { "was" } else { "were" }),
+            };
+            self.diag.print_formatted_at(DiagType::Error, span, message);
+            return TypeSpec::Invalid;
+        }
+        if expected == 0 {
+            return TypeSpec::Struct(key);
+        }
+        let args: Vec<TypeSpec> = args.iter().map(|arg| self.resolve_type(arg, span)).collect();
+        if args.contains(&TypeSpec::Invalid) {
+            return TypeSpec::Invalid;
+        }
+        TypeSpec::Instance(key, args)
+    }
+    fn resolve_return_type(&self, type_spec: &TypeSpec, span: Span) -> TypeSpec {
+        if *type_spec == TypeSpec::Void {
+            return TypeSpec::Void;
+        }
+        self.resolve_type(type_spec, span)
+    }
+    pub(super) fn enter_scope(&mut self, scope: ScopeId) {
+        self.scope_stack.push(self.current_scope);
+        self.current_scope = scope;
+    }
+    pub(super) fn leave_scope(&mut self) {
+        self.current_scope = self.scope_stack.pop().expect("Expected atleast 1 scope because we pushed atleast 1");
+    }
+    // Enters the scope collected for the function, block, `for` loop or `match` arm with id `node`
+    pub(super) fn enter_node_scope(&mut self, node: usize) {
+        let scope: ScopeId = self.node_scopes.get(&node).copied().unwrap_or(self.current_scope);
+        self.enter_scope(scope);
     }
     fn collect_func_decl(&mut self, func: FunctionDeclerationStatement) {
         let name: String = func.name().get_data();
-        if self.contains_name("__top_scope__".to_string(), name.clone()) {
-            self.diag.print_formatted(DiagType::Error, format!("Redefinition of function `{}`", name));
-        }
-        self.append_top(name.clone(), SymbolKind::Function(vec![], func.return_type()));
-        let func_scope: SymbolTable = SymbolTable::new(Some(self.current_scope.clone()));
-        // TODO: Function arguments
-        self.scope_names.push(self.current_scope.clone());
-        self.current_scope = name.clone();
-        self.add_table(name.clone(), func_scope);
-        self.collect_symbols(&func.body());
-        self.current_scope = self.scope_names.pop().expect("Expected atleast 1 scope because we pushed atleast 1");
+        if self.local(self.current_scope, &name).is_some() {
+            let mut diagnostic: Diagnostic = Diagnostic::new(DiagType::Error, format!("Redefinition of function `{}`", name)).with_span(func.name().get_span());
+            if let Some(previous) = self.local_location(self.current_scope, &name) {
+                diagnostic = diagnostic.with_label(previous, "previous definition here".to_string());
+            }
+            self.diag.emit(diagnostic);
+        }
+        match (func.is_external(), func.body()) {
+            (false, None) => {
+                self.diag.print_formatted_at(DiagType::Error, func.span(), format!("Function `{}` has no body, only `external` functions can be declared without one", name));
+            }
+            (true, Some(body)) => {
+                self.diag.print_formatted_at(DiagType::Error, body.span(), format!("External function `{}` can't have a body", name));
+            }
+            _ => {}
+        }
+        let param_types: Vec<TypeSpec> = func.params().iter().map(|param| self.resolve_type(&param.type_spec, param.span)).collect();
+        let return_type: TypeSpec = self.resolve_return_type(&func.return_type(), func.name().get_span());
+        let definition: DefinitionId = self.define_current(name.clone(), SymbolKind::Function(param_types.clone(), return_type), Some(func.name().get_span()));
+        // Every function gets its own scope, even one named like another scope or redefining a function
+        let func_scope: ScopeId = self.add_scope(ScopeKind::Function, self.scoped_name(&name));
+        self.scopes[func_scope].owner = Some(definition);
+        self.node_scopes.insert(func.get_id(), func_scope);
+        for (param, type_spec) in func.params().into_iter().zip(param_types) {
+            let param_name: String = param.name.get_data();
+            if let Some(previous) = self.local_location(func_scope, &param_name) {
+                self.diag.emit(Diagnostic::new(DiagType::Error, format!("Duplicate parameter `{}` in function `{}`", param_name, name))
+                    .with_span(param.name.get_span())
+                    .with_label(previous, "previous parameter here".to_string()));
+                continue;
+            }
+            self.define(func_scope, param_name, SymbolKind::Variable(type_spec), Some(param.name.get_span()));
+        }
+        self.enter_scope(func_scope);
+        if let Some(body) = func.body() {
+            self.collect_symbols(&body);
+        }
+        self.leave_scope();
+    }
+    // Namespaces and the types in them are declared before anything else is collected
+    // so signatures can use types declared further down the file
+    fn declare_types(&mut self, stmt: &StatementType) {
+        match stmt {
+            StatementType::Decleration(DeclerationType::Namespace(namespace)) => {
+                self.declare_namespace(namespace);
+            }
+            StatementType::Decleration(DeclerationType::Struct(struct_decl)) => {
+                self.declare_struct(struct_decl);
+            }
+            StatementType::Decleration(DeclerationType::TypeAlias(alias)) => {
+                self.declare_alias(alias);
+            }
+            StatementType::Decleration(DeclerationType::Enum(enum_decl)) => {
+                self.declare_enum(enum_decl);
+            }
+            _ => {}
+        }
+    }
+    fn declare_enum(&mut self, enum_decl: &EnumDeclerationStatement) {
+        let name: String = enum_decl.name().get_data();
+        if self.local(self.current_scope, &name).is_some() {
+            let mut diagnostic: Diagnostic = Diagnostic::new(DiagType::Error, format!("Redefinition of `{}`", name)).with_span(enum_decl.name().get_span());
+            if let Some(previous) = self.local_location(self.current_scope, &name) {
+                diagnostic = diagnostic.with_label(previous, "previous definition here".to_string());
+            }
+            self.diag.emit(diagnostic);
+            return;
+        }
+        let key: String = self.scoped_name(&name);
+        self.enums.insert(key.clone(), EnumDefinition { name: enum_decl.name(), variants: vec![] });
+        self.define_current(name, SymbolKind::Enum(key), Some(enum_decl.name().get_span()));
+    }
+    fn declare_alias(&mut self, alias: &TypeAliasDeclerationStatement) {
+        let name: String = alias.name().get_data();
+        if self.local(self.current_scope, &name).is_some() {
+            let mut diagnostic: Diagnostic = Diagnostic::new(DiagType::Error, format!("Redefinition of `{}`", name)).with_span(alias.name().get_span());
+            if let Some(previous) = self.local_location(self.current_scope, &name) {
+                diagnostic = diagnostic.with_label(previous, "previous definition here".to_string());
+            }
+            self.diag.emit(diagnostic);
+            return;
+        }
+        let key: String = self.scoped_name(&name);
+        self.aliases.insert(key.clone(), AliasDefinition { name: alias.name(), type_spec: alias.type_spec(), scope: self.current_scope, span: alias.span(), resolved: None });
+        self.define_current(name, SymbolKind::TypeAlias(key), Some(alias.name().get_span()));
+    }
+    // Resolves every alias before the symbols are collected, in source order so
+    // an alias that refers to itself is reported where it's first declared
+    fn resolve_aliases(&mut self) {
+        let mut keys: Vec<String> = self.aliases.keys().cloned().collect();
+        keys.sort_by_key(|key| self.aliases[key].span.start);
+        for key in keys {
+            self.resolve_alias(&key, &mut vec![]);
+        }
+    }
+    // Resolves the aliases named in `key`'s type first. `stack` holds the aliases being resolved,
+    // reaching one that is already on it means the alias refers to itself
+    fn resolve_alias(&mut self, key: &str, stack: &mut Vec<String>) {
+        let definition: AliasDefinition = self.aliases[key].clone();
+        if definition.resolved.is_some() {
+            return;
+        }
+        if let Some(cycle_start) = stack.iter().position(|member| member == key) {
+            let cycle: Vec<String> = stack[cycle_start..].to_vec();
+            let mut diagnostic: Diagnostic = Diagnostic::new(DiagType::Error, format!("Type alias `{}` refers to itself", key)).with_span(definition.name.get_span());
+            for (index, member) in cycle.iter().enumerate() {
+                let next: &String = cycle.get(index + 1).unwrap_or(&cycle[0]);
+                let label: String = if next == member { format!("`{}` refers to itself here", member) } else { format!("`{}` refers to `{}` here", member, next) };
+                diagnostic = diagnostic.with_label(self.aliases[member].span, label);
+            }
+            self.diag.emit(diagnostic);
+            for member in cycle {
+                if let Some(alias) = self.aliases.get_mut(&member) {
+                    alias.resolved = Some(TypeSpec::Invalid);
+                }
+            }
+            return;
+        }
+        stack.push(key.to_string());
+        self.enter_scope(definition.scope);
+        for dependency in self.alias_dependencies(&definition.type_spec) {
+            self.resolve_alias(&dependency, stack);
+        }
+        let resolved: TypeSpec = self.resolve_type(&definition.type_spec, definition.name.get_span());
+        self.leave_scope();
+        stack.pop();
+        // Members of a cycle were already marked as invalid when the cycle was found
+        if let Some(alias) = self.aliases.get_mut(key) && alias.resolved.is_none() {
+            alias.resolved = Some(resolved);
+        }
+    }
+    // Keys of the aliases a type annotation names, looked up from the current scope
+    fn alias_dependencies(&self, type_spec: &TypeSpec) -> Vec<String> {
+        match type_spec {
+            TypeSpec::Pointer(pointee) => self.alias_dependencies(pointee),
+            TypeSpec::Named(path) => match self.lookup_path(path) {
+                Some(SymbolKind::TypeAlias(key)) => vec![key],
+                _ => vec![],
+            },
+            TypeSpec::Generic(_, args) => args.iter().flat_map(|arg| self.alias_dependencies(arg)).collect(),
+            _ => vec![],
+        }
+    }
+    fn declare_struct(&mut self, struct_decl: &StructDeclerationStatement) {
+        let name: String = struct_decl.name().get_data();
+        if self.local(self.current_scope, &name).is_some() {
+            let mut diagnostic: Diagnostic = Diagnostic::new(DiagType::Error, format!("Redefinition of `{}`", name)).with_span(struct_decl.name().get_span());
+            if let Some(previous) = self.local_location(self.current_scope, &name) {
+                diagnostic = diagnostic.with_label(previous, "previous definition here".to_string());
+            }
+            self.diag.emit(diagnostic);
+            return;
+        }
+        let mut type_params: Vec<String> = vec![];
+        for (index, param) in struct_decl.type_params().iter().enumerate() {
+            if let Some(previous) = struct_decl.type_params()[..index].iter().find(|previous| previous.get_data() == param.get_data()) {
+                self.diag.emit(Diagnostic::new(DiagType::Error, format!("Duplicate type parameter `{}` in struct `{}`", param.get_data(), name))
+                    .with_span(param.get_span())
+                    .with_label(previous.get_span(), "previous parameter here".to_string()));
+            }
+            type_params.push(param.get_data());
+        }
+        let key: String = self.scoped_name(&name);
+        self.structs.insert(key.clone(), StructDefinition { name: struct_decl.name(), type_params, fields: vec![] });
+        self.define_current(name, SymbolKind::Struct(key), Some(struct_decl.name().get_span()));
+    }
+    fn declare_namespace(&mut self, namespace: &NamespaceDeclerationStatement) {
+        let name: String = namespace.name().get_data();
+        let scope: ScopeId = match self.get_local(self.current_scope, &name) {
+            // Namespaces can be reopened to add more declarations
+            Some(SymbolKind::Namespace(scope)) => scope,
+            Some(_) => {
+                let mut diagnostic: Diagnostic = Diagnostic::new(DiagType::Error, format!("Namespace `{}` conflicts with an existing declaration", name)).with_span(namespace.name().get_span());
+                if let Some(previous) = self.local_location(self.current_scope, &name) {
+                    diagnostic = diagnostic.with_label(previous, "previous declaration here".to_string());
+                }
+                self.diag.emit(diagnostic);
+                return;
+            }
+            None => {
+                let scope: ScopeId = self.add_scope(ScopeKind::Namespace, self.scoped_name(&name));
+                self.define_current(name, SymbolKind::Namespace(scope), Some(namespace.name().get_span()));
+                scope
+            }
+        };
+        self.enter_scope(scope);
+        for stmt in namespace.body() {
+            self.declare_types(&stmt);
+        }
+        self.leave_scope();
+    }
+    fn collect_namespace_decl(&mut self, namespace: &NamespaceDeclerationStatement) {
+        // A namespace conflicting with another declaration was reported by `declare_namespace`
+        let Some(SymbolKind::Namespace(scope)) = self.get_local(self.current_scope, &namespace.name().get_data()) else {
+            return;
+        };
+        self.enter_scope(scope);
+        for stmt in namespace.body() {
+            self.collect_symbols(&stmt);
+        }
+        self.leave_scope();
+    }
+    fn collect_struct_decl(&mut self, struct_decl: &StructDeclerationStatement) {
+        let key: String = self.scoped_name(&struct_decl.name().get_data());
+        // A redefinition was reported by `declare_struct`, keep the first definition's fields
+        if self.structs.get(&key).is_none_or(|definition| definition.name.get_span() != struct_decl.name().get_span()) {
+            return;
+        }
+        self.type_params = self.structs[&key].type_params.clone();
+        let mut fields: Vec<Field> = vec![];
+        for field in struct_decl.fields() {
+            if let Some(previous) = fields.iter().find(|previous| previous.name.get_data() == field.name.get_data()) {
+                self.diag.emit(Diagnostic::new(DiagType::Error, format!("Duplicate field `{}` in struct `{}`", field.name.get_data(), key))
+                    .with_span(field.name.get_span())
+                    .with_label(previous.name.get_span(), "previous field here".to_string()));
+                continue;
+            }
+            let type_spec: TypeSpec = self.resolve_type(&field.type_spec, field.span);
+            fields.push(Field { type_spec, ..field });
+        }
+        self.type_params.clear();
+        if let Some(definition) = self.structs.get_mut(&key) {
+            definition.fields = fields;
+        }
+    }
+    fn collect_enum_decl(&mut self, enum_decl: &EnumDeclerationStatement) {
+        let key: String = self.scoped_name(&enum_decl.name().get_data());
+        // A redefinition was reported by `declare_enum`, keep the first definition's variants
+        if self.enums.get(&key).is_none_or(|definition| definition.name.get_span() != enum_decl.name().get_span()) {
+            return;
+        }
+        let mut variants: Vec<Variant> = vec![];
+        for variant in enum_decl.variants() {
+            if let Some(previous) = variants.iter().find(|previous| previous.name.get_data() == variant.name.get_data()) {
+                self.diag.emit(Diagnostic::new(DiagType::Error, format!("Duplicate variant `{}` in enum `{}`", variant.name.get_data(), key))
+                    .with_span(variant.name.get_span())
+                    .with_label(previous.name.get_span(), "previous variant here".to_string()));
+                continue;
+            }
+            let fields: Vec<TypeSpec> = variant.fields.iter().map(|field| self.resolve_type(field, variant.span)).collect();
+            variants.push(Variant { fields, ..variant });
+        }
+        if let Some(definition) = self.enums.get_mut(&key) {
+            definition.variants = variants;
+        }
+    }
+    fn collect_var_decl(&mut self, var: &VariableDeclerationStatement) {
+        let name: String = var.name().get_data();
+        if let Some(previous) = self.local_location(self.current_scope, &name) {
+            self.diag.emit(Diagnostic::new(DiagType::Error, format!("Redeclaration of variable `{}` in the same scope", name))
+                .with_span(var.name().get_span())
+                .with_label(previous, "previous declaration here".to_string()));
+            return;
+        }
+        if let Some(shadowed) = self.shadowed_local(&name, var.name().get_span().start) {
+            self.diag.emit(Diagnostic::new(DiagType::Warning, format!("Variable `{}` shadows an earlier declaration in the same function", name))
+                .with_span(var.name().get_span())
+                .with_label(shadowed, "previous declaration here".to_string()));
+        }
+        let type_spec: TypeSpec = self.resolve_type(&var.type_spec(), var.name().get_span());
+        let id: DefinitionId = self.define_current(name, SymbolKind::Variable(type_spec), Some(var.name().get_span()));
+        // The initializer can't refer to the variable it initializes
+        self.definitions[id].visible_from = var.span().end;
+    }
+    // Where a variable of the enclosing function that a `var` declared at `position` would hide was
+    // declared. Globals may be shadowed freely, and so may anything by `for` loop and pattern bindings
+    fn shadowed_local(&self, name: &str, position: usize) -> Option<Span> {
+        let mut scope: ScopeId = self.current_scope;
+        while self.scopes[scope].kind == ScopeKind::Block {
+            scope = self.scopes[scope].parent?;
+            let Some(id) = self.local(scope, name) else {
+                continue;
+            };
+            let definition: &Definition = &self.definitions[id];
+            if matches!(definition.kind, SymbolKind::Variable(_)) && definition.visible_from <= position {
+                return definition.location;
+            }
+        }
+        None
     }
     fn collect_symbols(&mut self, stmt: &StatementType) {
         match stmt {
             StatementType::Decleration(DeclerationType::Function(func)) => {
                 self.collect_func_decl(*func.clone());
             }
+            StatementType::Decleration(DeclerationType::Variable(var)) => {
+                self.collect_var_decl(var);
+                if let Some(initializer) = var.initializer() {
+                    self.collect_match_scopes(&initializer);
+                }
+            }
+            StatementType::Decleration(DeclerationType::Namespace(namespace)) => {
+                self.collect_namespace_decl(namespace);
+            }
+            StatementType::Decleration(DeclerationType::Struct(struct_decl)) => {
+                self.collect_struct_decl(struct_decl);
+            }
+            StatementType::Decleration(DeclerationType::Enum(enum_decl)) => {
+                self.collect_enum_decl(enum_decl);
+            }
             StatementType::Block(block) => {
-                let block_scope_name = format!("__block_{}__", block.get_id());
-                let block_scope: SymbolTable = SymbolTable::new(Some(self.current_scope.clone()));
-                self.add_table(block_scope_name.clone(), block_scope);
-                self.scope_names.push(self.current_scope.clone());
-                self.current_scope = block_scope_name.clone();
+                let block_scope: ScopeId = self.add_scope(ScopeKind::Block, self.scopes[self.current_scope].path.clone());
+                self.node_scopes.insert(block.get_id(), block_scope);
+                self.enter_scope(block_scope);
                 for stmt in &block.body {
                     self.collect_symbols(stmt);
                 }
-                self.current_scope = self.scope_names.pop().expect("Expected atleast 1 scope because we pushed atleast 1");
+                self.leave_scope();
+            }
+            StatementType::Expr(expr, _) | StatementType::Return(Some(expr), _) => {
+                self.collect_match_scopes(expr);
+            }
+            StatementType::If(if_stmt) => {
+                self.collect_match_scopes(&if_stmt.condition());
+                self.collect_symbols(&if_stmt.then_branch());
+                if let Some(else_branch) = if_stmt.else_branch() {
+                    self.collect_symbols(&else_branch);
+                }
+            }
+            StatementType::While(while_stmt) => {
+                self.collect_match_scopes(&while_stmt.condition());
+                self.collect_symbols(&while_stmt.body());
+            }
+            StatementType::For(for_stmt) => {
+                self.collect_match_scopes(&for_stmt.iterable());
+                // The loop variable lives in its own scope around the body
+                let for_scope: ScopeId = self.add_scope(ScopeKind::Block, self.scopes[self.current_scope].path.clone());
+                let type_spec: TypeSpec = self.resolve_type(&for_stmt.type_spec(), for_stmt.variable().get_span());
+                self.define(for_scope, for_stmt.variable().get_data(), SymbolKind::Variable(type_spec), Some(for_stmt.variable().get_span()));
+                self.node_scopes.insert(for_stmt.get_id(), for_scope);
+                self.enter_scope(for_scope);
+                self.collect_symbols(&for_stmt.body());
+                self.leave_scope();
+            }
+            _ => {}
+        }
+    }
+    // Every arm of a `match` gets a scope for the names its pattern binds, which are added once the
+    // type of the matched value is known. Blocks in an arm's body are nested in that scope
+    fn collect_match_scopes(&mut self, expr: &ExprType) {
+        match expr {
+            ExprType::Match(match_expr) => {
+                self.collect_match_scopes(&match_expr.scrutinee());
+                for arm in match_expr.arms() {
+                    let arm_scope: ScopeId = self.add_scope(ScopeKind::Block, self.scopes[self.current_scope].path.clone());
+                    self.node_scopes.insert(arm.get_id(), arm_scope);
+                    self.enter_scope(arm_scope);
+                    self.collect_symbols(&arm.body());
+                    self.leave_scope();
+                }
+            }
+            ExprType::Unary(_, operand, _) | ExprType::Cast(operand, _, _) | ExprType::FieldAccess(operand, _, _) => {
+                self.collect_match_scopes(operand);
+            }
+            ExprType::Binary(left, _, right, _) | ExprType::Assign(left, _, right, _) => {
+                self.collect_match_scopes(left);
+                self.collect_match_scopes(right);
+            }
+            ExprType::Call(callee, args, _) => {
+                self.collect_match_scopes(callee);
+                for arg in args {
+                    self.collect_match_scopes(arg);
+                }
+            }
+            ExprType::StructLiteral(_, fields, _) => {
+                for field in fields {
+                    self.collect_match_scopes(&field.value);
+                }
             }
             _ => {}
         }
     }
     fn first_pass(&mut self) {
+        for stmt in self.ast.body.clone() {
+            self.declare_types(&stmt);
+        }
+        self.resolve_aliases();
         for stmt in self.ast.body.clone() {
             self.collect_symbols(&stmt);
         }
+        self.instantiate_generics();
+        self.compute_layouts();
+    }
+    // Monomorphization: every distinct instance of a generic struct named by a signature, a variable
+    // or a field gets its own definition in `structs`, keyed by how it's spelled, e.g. `Pair<int, u8>`,
+    // with the type parameters replaced by the arguments. Instances can name further instances
+    fn instantiate_generics(&mut self) {
+        let mut pending: Vec<TypeSpec> = vec![];
+        for definition in &self.definitions {
+            match &definition.kind {
+                SymbolKind::Variable(type_spec) => pending.push(type_spec.clone()),
+                SymbolKind::Function(param_types, return_type) => {
+                    pending.extend(param_types.iter().cloned());
+                    pending.push(return_type.clone());
+                }
+                _ => {}
+            }
+        }
+        pending.extend(self.aliases.values().filter_map(|alias| alias.resolved.clone()));
+        for definition in self.structs.values().filter(|definition| definition.type_params.is_empty()) {
+            pending.extend(definition.fields.iter().map(|field| field.type_spec.clone()));
+        }
+        for definition in self.enums.values() {
+            pending.extend(definition.variants.iter().flat_map(|variant| variant.fields.iter().cloned()));
+        }
+        let mut runaway: Vec<String> = vec![];
+        while let Some(type_spec) = pending.pop() {
+            let type_spec: TypeSpec = type_spec.canonical();
+            let TypeSpec::Instance(key, args) = &type_spec else {
+                if let TypeSpec::Pointer(pointee) = type_spec {
+                    pending.push(*pointee);
+                }
+                continue;
+            };
+            let instance_key: String = type_spec.to_string();
+            if self.structs.contains_key(&instance_key) || runaway.contains(key) {
+                continue;
+            }
+            let template: StructDefinition = self.structs[key].clone();
+            if instance_depth(&type_spec) > MAX_INSTANCE_DEPTH {
+                self.diag.print_formatted_at(DiagType::Error, template.name.get_span(), format!("Generic struct `{}` instantiates itself with ever deeper type arguments", key));
+                runaway.push(key.clone());
+                continue;
+            }
+            let fields: Vec<Field> = template.fields.iter()
+                .map(|field| Field { type_spec: substitute(&field.type_spec, &template.type_params, args), ..field.clone() })
+                .collect();
+            pending.extend(args.iter().cloned());
+            pending.extend(fields.iter().map(|field| field.type_spec.clone()));
+            self.structs.insert(instance_key, StructDefinition { name: template.name, type_params: vec![], fields });
+        }
+    }
+    // Lays out every struct and enum so later stages can look them up in `layouts`,
+    // in source order so recursive types are reported where they're first declared
+    fn compute_layouts(&mut self) {
+        // Generic structs only have layouts once instantiated
+        let mut keys: Vec<(String, Span)> = self.structs.iter()
+            .filter(|(_, definition)| definition.type_params.is_empty())
+            .map(|(key, definition)| (key.clone(), definition.name.get_span()))
+            .chain(self.enums.iter().map(|(key, definition)| (key.clone(), definition.name.get_span())))
+            .collect();
+        // Instances share their template's name, so they come after the types that use them
+        keys.sort_by_key(|(key, span)| (key.contains('<'), span.start));
+        for (key, _) in keys {
+            self.type_layout(&key, &mut vec![]);
+        }
+    }
+    // Layout of the struct or enum with the definition `key`. `path` holds the types being laid out and
+    // the field each one is currently at, reaching a type that is already on it means it contains itself by value
+    fn type_layout(&mut self, key: &str, path: &mut Vec<(String, Span)>) -> Option<Layout> {
+        if let Some(layout) = self.layouts.get(key) {
+            return layout.clone();
+        }
+        // The type of every field with where it's declared and which variant it belongs to, structs have one
+        let (name, kind, fields): (Token, &str, Vec<(TypeSpec, Span, usize)>) = if let Some(definition) = self.structs.get(key) {
+            (definition.name.clone(), "struct", definition.fields.iter().map(|field| (field.type_spec.clone(), field.span, 0)).collect())
+        } else {
+            let definition: &EnumDefinition = self.enums.get(key)?;
+            let fields = definition.variants.iter().enumerate().flat_map(|(index, variant)| variant.fields.iter().map(move |field| (field.clone(), variant.span, index)));
+            (definition.name.clone(), "enum", fields.collect())
+        };
+        if let Some(cycle_start) = path.iter().position(|(member, _)| member == key) {
+            let cycle: Vec<(String, Span)> = path[cycle_start..].to_vec();
+            let mut diagnostic: Diagnostic = Diagnostic::new(DiagType::Error, format!("Recursive {} `{}` has infinite size", kind, key)).with_span(name.get_span());
+            for (index, (member, field_span)) in cycle.iter().enumerate() {
+                let contained: &String = cycle.get(index + 1).map(|(next, _)| next).unwrap_or(&cycle[0].0);
+                diagnostic = diagnostic.with_label(*field_span, format!("`{}` stores `{}` by value here", member, contained));
+            }
+            self.diag.emit(diagnostic);
+            for (member, _) in cycle {
+                self.layouts.insert(member, None);
+            }
+            return None;
+        }
+        path.push((key.to_string(), name.get_span()));
+        let mut field_layouts: Option<Vec<(Layout, usize)>> = Some(vec![]);
+        for (type_spec, span, variant) in fields {
+            if let Some(last) = path.last_mut() {
+                last.1 = span;
+            }
+            let field_layout: Option<Layout> = match type_spec.canonical() {
+                TypeSpec::Struct(field_key) | TypeSpec::Enum(field_key) => self.type_layout(&field_key, path),
+                instance @ TypeSpec::Instance(..) => self.type_layout(&instance.to_string(), path),
+                type_spec => builtin_layout(&type_spec),
+            };
+            match (field_layouts.as_mut(), field_layout) {
+                (Some(layouts), Some(layout)) => layouts.push((layout, variant)),
+                _ => field_layouts = None,
+            }
+        }
+        path.pop();
+        // Members of a cycle were already marked as unsized when the cycle was found
+        if self.layouts.contains_key(key) {
+            return None;
+        }
+        let layout: Option<Layout> = field_layouts.map(|layouts| match self.enums.get(key) {
+            Some(definition) => {
+                let payloads: Vec<Layout> = (0..definition.variants.len())
+                    .map(|index| Layout::of_struct(&layouts.iter().filter(|(_, variant)| *variant == index).map(|(layout, _)| layout.clone()).collect::<Vec<Layout>>()))
+                    .collect();
+                Layout::of_enum(&payloads)
+            }
+            None => Layout::of_struct(&layouts.into_iter().map(|(layout, _)| layout).collect::<Vec<Layout>>()),
+        });
+        self.layouts.insert(key.to_string(), layout.clone());
+        layout
+    }
+    // Layout of any resolved type, `None` if it has none or couldn't be computed
+    pub fn layout_of(&self, type_spec: &TypeSpec) -> Option<Layout> {
+        match type_spec.canonical() {
+            TypeSpec::Struct(key) | TypeSpec::Enum(key) => self.layouts.get(&key).cloned().flatten(),
+            instance @ TypeSpec::Instance(..) => self.layouts.get(&instance.to_string()).cloned().flatten(),
+            _ => builtin_layout(type_spec),
+        }
     }
-    fn evaluate_expr_to_type(&self, expr: ExprType) -> TypeSpec {
+    pub(super) fn evaluate_expr_to_type(&self, expr: ExprType) -> TypeSpec {
         match expr {
-            ExprType::NumericLiteral(_) => {
-                return TypeSpec::Int;
+            ExprType::NumericLiteral(literal, span) => {
+                return self.integer_literal_type(&literal, false, span);
             }
-            ExprType::StringLiteral(_) => {
+            ExprType::FloatLiteral(literal, span) => {
+                return self.float_literal_type(&literal, span);
+            }
+            ExprType::StringLiteral(_, _) => {
                 return  TypeSpec::String;
             }
-            ExprType::Binary(left, op, right) => {
-                let left_type: TypeSpec = self.evaluate_expr_to_type(*left);
-                let right_type: TypeSpec = self.evaluate_expr_to_type(*right);
-                if left_type != right_type {
-                    self.diag.print_formatted(DiagType::Error, format!("Invalid operands of types `{:?}` and `{:?}` to binary operator (`{}`)", left_type, right_type, op.get_data()));
+            ExprType::NullLiteral(_, _) => {
+                return TypeSpec::Null;
+            }
+            ExprType::CharLiteral(_, _) => {
+                return TypeSpec::Char;
+            }
+            ExprType::BoolLiteral(_, _) => {
+                return TypeSpec::Bool;
+            }
+            ExprType::Identifier(identifier, _, span) => {
+                return match self.lookup_visible(&identifier.get_data(), span.start) {
+                    Some(SymbolKind::Variable(type_spec)) => type_spec,
+                    Some(_) => {
+                        self.diag.print_formatted_at(DiagType::Error, span, format!("`{}` is not a value", identifier.get_data()));
+                        TypeSpec::Invalid
+                    }
+                    // Undeclared identifiers are reported by `validate_expr`
+                    None => TypeSpec::Invalid,
+                };
+            }
+            ExprType::Assign(target, _, _, _) => {
+                // Invalid targets are reported by `check_assignment`, which also already
+                // evaluated field targets so they aren't evaluated again here
+                if let Some(SymbolKind::Variable(type_spec)) = self.resolve_path(&target) {
+                    return type_spec;
                 }
-                return left_type;
+                return TypeSpec::Invalid;
             }
-            _ => {
-                self.diag.print_formatted(DiagType::Ice, format!("Handle getting type for expression `{:?}`", expr));
+            ExprType::FieldAccess(base, field, _) => {
+                let base_span: Span = base.span();
+                let base_type: TypeSpec = self.evaluate_expr_to_type(*base);
+                return match base_type.canonical() {
+                    canonical @ (TypeSpec::Struct(_) | TypeSpec::Instance(..)) => match self.field_type(&Self::struct_key(&canonical), &field.get_data()) {
+                        Some(type_spec) => type_spec,
+                        None => {
+                            self.diag.print_formatted_at(DiagType::Error, field.get_span(), format!("No field named `{}` in struct `{}`", field.get_data(), base_type));
+                            TypeSpec::Invalid
+                        }
+                    },
+                    TypeSpec::Invalid => TypeSpec::Invalid,
+                    _ => {
+                        self.diag.emit(Diagnostic::new(DiagType::Error, format!("Type `{}` has no fields", base_type))
+                            .with_span(field.get_span())
+                            .with_label(base_span, format!("this is `{}`", base_type)));
+                        TypeSpec::Invalid
+                    }
+                };
+            }
+            ExprType::StructLiteral(name, _, _) => {
+                // The fields are checked by `check_struct_literal`
+                if let Some(symbol) = self.resolve_path(&name) && let Some(literal_type) = self.struct_literal_type(&symbol, &name)
+                    && self.structs.get(&Self::struct_key(&literal_type)).is_some_and(|definition| definition.type_params.is_empty()) {
+                    return literal_type;
+                }
+                return TypeSpec::Invalid;
+            }
+            ExprType::Call(callee, _, _) => {
+                // The arguments are checked by `check_call_arguments`
+                return match self.resolve_path(&callee) {
+                    Some(SymbolKind::Function(_, return_type)) => return_type,
+                    Some(SymbolKind::Variant(key, _)) => TypeSpec::Enum(key),
+                    _ => TypeSpec::Invalid,
+                };
+            }
+            ExprType::MemberAccess(_, _, span) => {
+                return match self.resolve_path(&expr) {
+                    Some(SymbolKind::Variable(type_spec)) => type_spec,
+                    Some(SymbolKind::Variant(key, index)) if self.enums[&key].variants[index].fields.is_empty() => TypeSpec::Enum(key),
+                    Some(SymbolKind::Variant(..)) => {
+                        self.diag.print_formatted_at(DiagType::Error, span, format!("Variant `{}` carries data, build it with `{}(...)`", Self::path_name(&expr), Self::path_name(&expr)));
+                        TypeSpec::Invalid
+                    }
+                    Some(_) => {
+                        self.diag.print_formatted_at(DiagType::Error, span, format!("`{}` is not a value", Self::path_name(&expr)));
+                        TypeSpec::Invalid
+                    }
+                    // Unknown names are reported by `validate_expr`
+                    None => TypeSpec::Invalid,
+                };
+            }
+            // Set by `check_match`, which already evaluated the arms
+            ExprType::Match(match_expr) => {
+                return self.match_types.get(&match_expr.get_id()).cloned().unwrap_or(TypeSpec::Invalid);
+            }
+            ExprType::Unary(op, operand, _) if matches!(op.get_type(), TokenType::Minus | TokenType::Bang | TokenType::Tilde) => {
+                let operand_span: Span = operand.span();
+                // `-9223372036854775808` is in range even though the literal on its own isn't
+                let operand_type: TypeSpec = match *operand {
+                    ExprType::NumericLiteral(literal, _) if op.get_type() == TokenType::Minus => self.integer_literal_type(&literal, true, op.get_span().merge(operand_span)),
+                    operand => self.evaluate_expr_to_type(operand),
+                };
+                // `!` takes anything a condition can be, `-` and `~` keep the operand's type
+                let valid: bool = match op.get_type() {
+                    TokenType::Bang => is_condition_type(&operand_type),
+                    TokenType::Minus => is_integer(&operand_type) || is_float(&operand_type),
+                    _ => is_integer(&operand_type),
+                };
+                if !valid && operand_type != TypeSpec::Invalid {
+                    self.diag.emit(Diagnostic::new(DiagType::Error, format!("Invalid operand of type `{}` to unary operator (`{}`)", operand_type, op.get_data()))
+                        .with_span(op.get_span())
+                        .with_label(operand_span, format!("this operand is `{}`", operand_type)));
+                    return TypeSpec::Invalid;
+                }
+                if op.get_type() == TokenType::Bang && operand_type != TypeSpec::Invalid {
+                    return TypeSpec::Bool;
+                }
+                return operand_type;
+            }
+            ExprType::Unary(op, operand, _) if op.get_type() == TokenType::Star => {
+                let operand_span: Span = operand.span();
+                let operand_type: TypeSpec = self.evaluate_expr_to_type(*operand);
+                return match operand_type.canonical() {
+                    TypeSpec::Pointer(pointee) => *pointee,
+                    TypeSpec::Invalid => TypeSpec::Invalid,
+                    TypeSpec::Null => {
+                        self.diag.print_formatted_at(DiagType::Error, operand_span, "Cannot dereference `null`".to_string());
+                        TypeSpec::Invalid
+                    }
+                    _ => {
+                        self.diag.emit(Diagnostic::new(DiagType::Error, format!("Cannot dereference a value of type `{}`, only pointers can be dereferenced", operand_type))
+                            .with_span(op.get_span())
+                            .with_label(operand_span, format!("this operand is `{}`", operand_type)));
+                        TypeSpec::Invalid
+                    }
+                };
+            }
+            // `&`, the last of the prefix operators
+            ExprType::Unary(_, operand, _) => {
+                // Taking the address of something that isn't a place is reported by `validate_expr`
+                return match self.evaluate_expr_to_type(*operand) {
+                    TypeSpec::Invalid => TypeSpec::Invalid,
+                    operand_type => TypeSpec::Pointer(Box::new(operand_type)),
+                };
+            }
+            ExprType::Binary(left, op, right, _) => {
+                let (left_span, right_span): (Span, Span) = (left.span(), right.span());
+                // An unsuffixed literal takes the integer type of the other operand, except for shifts
+                // where the operands don't need to match
+                let shift: bool = matches!(op.get_type(), TokenType::LessLess | TokenType::GreaterGreater);
+                let (left_type, right_type): (TypeSpec, TypeSpec) = match (Self::is_unsuffixed_literal(&left), Self::is_unsuffixed_literal(&right)) {
+                    (true, false) if !shift => {
+                        let right_type: TypeSpec = self.evaluate_expr_to_type(*right);
+                        (self.evaluate_expr_as(*left, &right_type), right_type)
+                    }
+                    (false, true) => {
+                        let left_type: TypeSpec = self.evaluate_expr_to_type(*left);
+                        let right_type: TypeSpec = if shift { self.evaluate_expr_to_type(*right) } else { self.evaluate_expr_as(*right, &left_type) };
+                        (left_type, right_type)
+                    }
+                    _ => (self.evaluate_expr_to_type(*left), self.evaluate_expr_to_type(*right)),
+                };
+                if left_type == TypeSpec::Invalid || right_type == TypeSpec::Invalid {
+                    return TypeSpec::Invalid;
+                }
+                let Some(result_type) = binary_type(op.get_type(), &left_type, &right_type) else {
+                    self.diag.emit(Diagnostic::new(DiagType::Error, format!("Invalid operands of types `{}` and `{}` to binary operator (`{}`){}", left_type, right_type, op.get_data(), conversion_hint(&left_type, &right_type)))
+                        .with_span(op.get_span())
+                        .with_label(left_span, format!("this operand is `{}`", left_type))
+                        .with_label(right_span, format!("this operand is `{}`", right_type)));
+                    return TypeSpec::Invalid;
+                };
+                return result_type;
+            }
+            ExprType::Cast(value, type_spec, span) => {
+                let value_span: Span = value.span();
+                let value_type: TypeSpec = self.evaluate_expr_to_type(*value);
+                let target_type: TypeSpec = self.resolve_type(&type_spec, span);
+                if !is_valid_cast(&value_type, &target_type) {
+                    self.diag.emit(Diagnostic::new(DiagType::Error, format!("Cannot cast a value of type `{}` to `{}`{}", value_type, target_type, cast_hint(&value_type, &target_type)))
+                        .with_span(span)
+                        .with_label(value_span, format!("this is `{}`", value_type)));
+                }
+                return target_type;
             }
+            // Parse errors stop before sema, so this is never reached
+            ExprType::Invalid(_) => {}
         }
-        unreachable!();
+        TypeSpec::Invalid
     }
-    fn get_return_types_in_block(&self, body: StatementType) -> Vec<TypeSpec> {
-        let mut stmts: Vec<StatementType> = vec![];
-        match body {
-            StatementType::Block(block) => {
-                stmts = block.body;
+    // Type of an integer literal, the one named by its suffix or `int`
+    fn integer_literal_type(&self, literal: &Token, negated: bool, span: Span) -> TypeSpec {
+        let text: String = literal.get_data();
+        let (value, suffix) = integer_literal_value(&text);
+        let integer_type = if suffix.is_empty() {
+            INTEGER_TYPES.first()
+        } else {
+            INTEGER_TYPES.iter().find(|(name, _, _, _)| *name == suffix)
+        };
+        let Some((_, type_spec, min, max)) = integer_type else {
+            self.diag.print_formatted_at(DiagType::Error, literal.get_span(), format!("Invalid suffix `{}` for an integer literal", suffix));
+            return TypeSpec::Invalid;
+        };
+        let value: Option<i128> = value.and_then(|value| i128::try_from(value).ok()).map(|value| if negated { -value } else { value });
+        if !value.is_some_and(|value| (*min..=*max).contains(&value)) {
+            self.diag.print_formatted_at(DiagType::Error, span, format!("Literal out of range for type `{}`, which holds `{}..={}`", type_spec, min, max));
+        }
+        type_spec.clone()
+    }
+    // Value of an integer literal without a suffix, possibly negated, `None` if `expr` isn't one.
+    // The inner `None` is for values too large for any integer type, which were already reported
+    fn unsuffixed_literal_value(expr: &ExprType) -> Option<Option<i128>> {
+        match expr {
+            ExprType::NumericLiteral(literal, _) => {
+                let text: String = literal.get_data();
+                let (value, suffix) = integer_literal_value(&text);
+                suffix.is_empty().then(|| value.and_then(|value| i128::try_from(value).ok()))
             }
+            ExprType::Unary(op, operand, _) if op.get_type() == TokenType::Minus => Self::unsuffixed_literal_value(operand).map(|value| value.map(|value| -value)),
+            _ => None,
+        }
+    }
+    // Type of `expr` where a value of type `expected` is wanted. Unsuffixed literals take on
+    // `expected` when it's an integer or float type of their kind, so `var x: u8 = 255;` needs no suffix
+    pub(super) fn evaluate_expr_as(&self, expr: ExprType, expected: &TypeSpec) -> TypeSpec {
+        // A `match` whose arms are all unsuffixed literals is one too
+        if let ExprType::Match(match_expr) = &expr && is_numeric(expected) && let Some(literals) = Self::literal_arms(match_expr) {
+            let arm_types: Vec<TypeSpec> = literals.into_iter().map(|literal| self.evaluate_expr_as(literal, expected)).collect();
+            if arm_types.iter().all(|arm_type| arm_type == expected) {
+                return expected.clone();
+            }
+            return self.evaluate_expr_to_type(expr);
+        }
+        if is_float(expected) && let Some(value) = Self::unsuffixed_float_value(&expr) {
+            self.check_float_range(value, expected, expr.span());
+            return expected.clone();
+        }
+        let (Some(value), Some((min, max))) = (Self::unsuffixed_literal_value(&expr), integer_range(expected)) else {
+            return self.evaluate_expr_to_type(expr);
+        };
+        if !value.is_some_and(|value| (min..=max).contains(&value)) {
+            self.diag.print_formatted_at(DiagType::Error, expr.span(), format!("Literal out of range for type `{}`, which holds `{}..={}`", expected, min, max));
+        }
+        expected.clone()
+    }
+    // Type of a float literal, the one named by its suffix or `f64`
+    fn float_literal_type(&self, literal: &Token, span: Span) -> TypeSpec {
+        let text: String = literal.get_data();
+        let (value, suffix) = float_literal_value(&text);
+        let type_spec: TypeSpec = match suffix {
+            "" | "f64" => TypeSpec::F64,
+            "f32" => TypeSpec::F32,
             _ => {
-                self.diag.print_formatted(DiagType::Ice, format!("Unhandled function body statement `{:?}`", body));
+                self.diag.print_formatted_at(DiagType::Error, literal.get_span(), format!("Invalid suffix `{}` for a float literal", suffix));
+                return TypeSpec::Invalid;
             }
         };
-        let mut return_types: Vec<TypeSpec> = vec![];
-        for stmt in stmts {
-            if let StatementType::Return(expr) = stmt {
-                let expr_type: TypeSpec = self.evaluate_expr_to_type(expr);
-                return_types.push(expr_type);
+        self.check_float_range(value, &type_spec, span);
+        type_spec
+    }
+    fn check_float_range(&self, value: Option<f64>, type_spec: &TypeSpec, span: Span) {
+        let in_range: bool = value.is_some_and(|value| if *type_spec == TypeSpec::F32 { (value as f32).is_finite() } else { value.is_finite() });
+        if !in_range {
+            self.diag.print_formatted_at(DiagType::Error, span, format!("Literal out of range for type `{}`", type_spec));
+        }
+    }
+    // Value of a float literal without a suffix, possibly negated, `None` if `expr` isn't one
+    fn unsuffixed_float_value(expr: &ExprType) -> Option<Option<f64>> {
+        match expr {
+            ExprType::FloatLiteral(literal, _) => {
+                let text: String = literal.get_data();
+                let (value, suffix) = float_literal_value(&text);
+                suffix.is_empty().then_some(value)
+            }
+            ExprType::Unary(op, operand, _) if op.get_type() == TokenType::Minus => Self::unsuffixed_float_value(operand).map(|value| value.map(|value| -value)),
+            _ => None,
+        }
+    }
+    // Whether `expr` is a literal whose type depends on where it's used
+    pub(super) fn is_unsuffixed_literal(expr: &ExprType) -> bool {
+        Self::unsuffixed_literal_value(expr).is_some() || Self::unsuffixed_float_value(expr).is_some()
+    }
+    // The bodies of a `match` if every arm is an unsuffixed literal
+    fn literal_arms(match_expr: &MatchExpression) -> Option<Vec<ExprType>> {
+        let arms: Vec<ExprType> = match_expr.arms().into_iter().map(|arm| match arm.body() {
+            StatementType::Expr(expr, _) if Self::is_unsuffixed_literal(&expr) => Some(expr),
+            _ => None,
+        }).collect::<Option<Vec<ExprType>>>()?;
+        (!arms.is_empty()).then_some(arms)
+    }
+    // Symbol named by an identifier, a `namespace::name` path or an `Enum::Variant` path, if it exists
+    pub(super) fn resolve_path(&self, expr: &ExprType) -> Option<SymbolKind> {
+        match expr {
+            ExprType::Identifier(identifier, _, span) => self.lookup_visible(&identifier.get_data(), span.start),
+            ExprType::MemberAccess(member, property, _) => {
+                let ExprType::Identifier(property_ident, _, _) = &**property else {
+                    return None;
+                };
+                match self.resolve_path(member)? {
+                    SymbolKind::Namespace(scope) => self.get_local(scope, &property_ident.get_data()),
+                    symbol => {
+                        let key: String = self.enum_key(&symbol)?;
+                        let index: usize = self.enums.get(&key)?.variants.iter().position(|variant| variant.name.get_data() == property_ident.get_data())?;
+                        Some(SymbolKind::Variant(key, index))
+                    }
+                }
+            }
+            _ => None,
+        }
+    }
+    // Key of the enum `symbol` names, directly or through an alias
+    fn enum_key(&self, symbol: &SymbolKind) -> Option<String> {
+        match symbol {
+            SymbolKind::Enum(key) => Some(key.clone()),
+            SymbolKind::TypeAlias(key) => match self.aliases.get(key)?.resolved.as_ref()?.canonical() {
+                TypeSpec::Enum(key) => Some(key),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+    // Type a struct literal naming `symbol` builds, aliases of structs can be used
+    // as well, so `type IntPair = Pair<int, int>;` allows `IntPair { ... }`
+    fn struct_literal_type(&self, symbol: &SymbolKind, name: &ExprType) -> Option<TypeSpec> {
+        match symbol {
+            SymbolKind::Struct(key) => Some(TypeSpec::Struct(key.clone())),
+            SymbolKind::TypeAlias(key) => {
+                let aliased: TypeSpec = self.aliases.get(key)?.resolved.clone()?;
+                matches!(aliased.canonical(), TypeSpec::Struct(_) | TypeSpec::Instance(..)).then(|| TypeSpec::Alias(Self::path_name(name), Box::new(aliased)))
+            }
+            _ => None,
+        }
+    }
+    // Key of the definition of a struct or an instance of a generic struct in `structs`
+    pub(super) fn struct_key(type_spec: &TypeSpec) -> String {
+        match type_spec.canonical() {
+            TypeSpec::Struct(key) => key,
+            canonical => canonical.to_string(),
+        }
+    }
+    fn field_type(&self, key: &str, field: &str) -> Option<TypeSpec> {
+        let definition: &StructDefinition = self.structs.get(key)?;
+        definition.fields.iter().find(|candidate| candidate.name.get_data() == field).map(|candidate| candidate.type_spec.clone())
+    }
+    fn check_struct_literal(&mut self, name: &ExprType, fields: &[FieldInitializer], span: Span) {
+        for field in fields {
+            self.validate_expr(field.value.clone());
+        }
+        let Some(symbol) = self.resolve_path(name) else {
+            return;
+        };
+        let key: String = match self.struct_literal_type(&symbol, name) {
+            Some(literal_type) => Self::struct_key(&literal_type),
+            // An alias that couldn't be resolved was already reported
+            None if matches!(&symbol, SymbolKind::TypeAlias(key) if self.aliases[key].resolved == Some(TypeSpec::Invalid)) => return,
+            None => {
+                self.diag.print_formatted_at(DiagType::Error, name.span(), format!("`{}` is not a struct", Self::path_name(name)));
+                return;
+            }
+        };
+        // Instances that couldn't be instantiated were already reported
+        let Some(definition) = self.structs.get(&key) else {
+            return;
+        };
+        if !definition.type_params.is_empty() {
+            self.diag.print_formatted_at(DiagType::Error, name.span(), format!("Generic struct `{}` can't be built with a struct literal, declare a variable of one of its instances and assign the fields instead", Self::path_name(name)));
+            return;
+        }
+        let mut initialized: Vec<&FieldInitializer> = vec![];
+        for field in fields {
+            let field_name: String = field.name.get_data();
+            if let Some(previous) = initialized.iter().find(|previous| previous.name.get_data() == field_name) {
+                self.diag.emit(Diagnostic::new(DiagType::Error, format!("Field `{}` is initialized more than once", field_name))
+                    .with_span(field.name.get_span())
+                    .with_label(previous.span, "first initialized here".to_string()));
+                continue;
+            }
+            initialized.push(field);
+            let Some(expected_type) = self.field_type(&key, &field_name) else {
+                self.diag.print_formatted_at(DiagType::Error, field.name.get_span(), format!("No field named `{}` in struct `{}`", field_name, key));
+                continue;
             };
+            let actual_type: TypeSpec = self.evaluate_expr_as(field.value.clone(), &expected_type);
+            if !is_assignable(&actual_type, &expected_type) {
+                self.diag.print_formatted_at(DiagType::Error, field.value.span(), format!("Mismatched types, field `{}` is `{}` but got `{}`{}", field_name, expected_type, actual_type, conversion_hint(&actual_type, &expected_type)));
+            }
+        }
+        let missing: Vec<String> = self.structs[&key].fields.iter()
+            .map(|field| field.name.get_data())
+            .filter(|field_name| !initialized.iter().any(|field| field.name.get_data() == *field_name))
+            .map(|field_name| format!("`{}`", field_name))
+            .collect();
+        if !missing.is_empty() {
+            self.diag.print_formatted_at(DiagType::Error, span, format!("Missing field{} {} in initializer of struct `{}`", if missing.len() == 1 { "" } else { "s" }, missing.join(", "), key));
         }
-        return_types
     }
-    fn validate_func(&mut self, func: FunctionDeclerationStatement) {
-        let return_type_expected: TypeSpec = func.return_type();
-        let return_type_actual: Vec<TypeSpec> = self.get_return_types_in_block(func.body());
-        for actual_type in return_type_actual {
-            if return_type_expected != actual_type {
-                self.diag.print_formatted(DiagType::Error, format!("Unexpected return type, expected `{:?}` but got `{:?}`", return_type_expected, actual_type));
+    fn path_name(expr: &ExprType) -> String {
+        match expr {
+            ExprType::Identifier(identifier, _, _) => identifier.get_data(),
+            ExprType::MemberAccess(member, property, _) => format!("{}::{}", Self::path_name(member), Self::path_name(property)),
+            _ => "<expression>".to_string(),
+        }
+    }
+    fn check_call_arguments(&mut self, callee: &ExprType, args: &[ExprType], span: Span) {
+        let param_types: Vec<TypeSpec> = match self.resolve_path(callee) {
+            Some(SymbolKind::Function(param_types, _)) => param_types,
+            Some(SymbolKind::Variant(key, index)) => {
+                let fields: Vec<TypeSpec> = self.enums[&key].variants[index].fields.clone();
+                if fields.is_empty() {
+                    self.diag.print_formatted_at(DiagType::Error, span, format!("Variant `{}` carries no data, write it without `()`", Self::path_name(callee)));
+                    return;
+                }
+                if fields.len() != args.len() {
+                    self.diag.print_formatted_at(DiagType::Error, span, format!("Variant `{}` has {} field{} but {} {} given", Self::path_name(callee), fields.len(), if fields.len() == 1 { "" } else { "s" }, args.len(), if args.len() == 1 { "was" } else { "were" }));
+                    return;
+                }
+                fields
+            }
+            Some(_) => {
+                self.diag.print_formatted_at(DiagType::Error, callee.span(), format!("`{}` is not a function", Self::path_name(callee)));
+                return;
+            }
+            None => return,
+        };
+        if param_types.len() != args.len() {
+            self.diag.print_formatted_at(DiagType::Error, span, format!("Function `{}` expects {} argument{} but {} {} given", Self::path_name(callee), param_types.len(), if param_types.len() == 1 { "" } else { "s" }, args.len(), if args.len() == 1 { "was" } else { "were" }));
+            return;
+        }
+        for (arg, expected_type) in args.iter().zip(param_types) {
+            let actual_type: TypeSpec = self.evaluate_expr_as(arg.clone(), &expected_type);
+            if !is_assignable(&actual_type, &expected_type) {
+                self.diag.print_formatted_at(DiagType::Error, arg.span(), format!("Mismatched argument type, expected `{}` but got `{}`{}", expected_type, actual_type, conversion_hint(&actual_type, &expected_type)));
+            }
+        }
+    }
+    // Whether `expr` has the shape of something with an address, what it names isn't checked
+    fn is_place(expr: &ExprType) -> bool {
+        match expr {
+            ExprType::Identifier(..) => true,
+            ExprType::FieldAccess(base, _, _) => Self::is_place(base),
+            ExprType::Unary(op, _, _) => op.get_type() == TokenType::Star,
+            _ => false,
+        }
+    }
+    // Whether `target` names storage that can be assigned to: a variable, a dereferenced
+    // pointer or a field of either
+    fn check_place(&mut self, target: &ExprType) -> bool {
+        match target {
+            ExprType::Identifier(identifier, _, span) => match self.lookup_visible(&identifier.get_data(), span.start) {
+                Some(SymbolKind::Variable(_)) => true,
+                Some(_) => {
+                    self.diag.print_formatted_at(DiagType::Error, *span, format!("Cannot assign to `{}` because it isn't a variable", identifier.get_data()));
+                    false
+                }
+                None => false,
+            },
+            ExprType::FieldAccess(base, _, _) if Self::is_place(base) => self.check_place(base),
+            ExprType::Unary(op, _, _) if op.get_type() == TokenType::Star => true,
+            _ => {
+                self.diag.print_formatted_at(DiagType::Error, target.span(), "Invalid left-hand side of assignment".to_string());
+                false
+            }
+        }
+    }
+    fn check_assignment(&mut self, target: &ExprType, op: &Token, value: &ExprType) {
+        if !self.check_place(target) {
+            return;
+        }
+        let target_type: TypeSpec = self.evaluate_expr_to_type(target.clone());
+        let value_type: TypeSpec = self.evaluate_expr_as(value.clone(), &target_type);
+        if !is_assignable(&value_type, &target_type) {
+            self.diag.emit(Diagnostic::new(DiagType::Error, format!("Mismatched types, cannot assign `{}` to a variable of type `{}` (`{}`){}", value_type, target_type, op.get_data(), conversion_hint(&value_type, &target_type)))
+                .with_span(value.span())
+                .with_label(target.span(), format!("this is `{}`", target_type)));
+        }
+    }
+    // Checks a `return` against the signature of the function it's in
+    fn check_return(&mut self, expr: Option<ExprType>, span: Span) {
+        if let Some(expr) = &expr {
+            self.validate_expr(expr.clone());
+        }
+        let Some((name, expected)) = self.current_function.clone() else {
+            return;
+        };
+        match (expr, &expected) {
+            (Some(expr), TypeSpec::Void) => {
+                self.diag.print_formatted_at(DiagType::Error, expr.span(), format!("Function `{}` returns `void` and can't return a value", name));
+            }
+            (None, TypeSpec::Void | TypeSpec::Invalid) => {}
+            (None, _) => {
+                self.diag.print_formatted_at(DiagType::Error, span, format!("Function `{}` must return a value of type `{}`", name, expected));
+            }
+            (Some(expr), _) => {
+                let expr_span: Span = expr.span();
+                let actual: TypeSpec = self.evaluate_expr_as(expr, &expected);
+                if !is_assignable(&actual, &expected) {
+                    self.diag.print_formatted_at(DiagType::Error, expr_span, format!("Unexpected return type, expected `{}` but got `{}`{}", expected, actual, conversion_hint(&actual, &expected)));
+                }
             }
         }
-        self.validate_stmt(func.body());
     }
+    fn validate_func(&mut self, func: FunctionDeclerationStatement) {
+        let Some(body) = func.body() else {
+            return;
+        };
+        // The function's own definition, a redefinition doesn't replace the return type of the first
+        let Some(&func_scope) = self.node_scopes.get(&func.get_id()) else {
+            return;
+        };
+        let Some(SymbolKind::Function(_, return_type)) = self.scopes[func_scope].owner.map(|owner| self.definitions[owner].kind.clone()) else {
+            return;
+        };
+        let name: String = func.name().get_data();
+        self.enter_scope(func_scope);
+        let enclosing: Option<(String, TypeSpec)> = self.current_function.replace((name.clone(), return_type.clone()));
+        self.validate_stmt(body.clone());
+        self.current_function = enclosing;
+        self.leave_scope();
+        let graph: ControlFlowGraph = ControlFlowGraph::build(&body, &self.non_exhaustive_matches);
+        for (unreachable, cause) in graph.unreachable() {
+            self.diag.emit(Diagnostic::new(DiagType::Warning, "Unreachable code".to_string())
+                .with_span(unreachable)
+                .with_label(cause, "any code following this statement is unreachable".to_string()));
+        }
+        if !matches!(return_type, TypeSpec::Void | TypeSpec::Invalid) && graph.falls_off_end() {
+            self.diag.emit(Diagnostic::new(DiagType::Error, format!("Not all paths of function `{}` return a value of type `{}`", name, return_type))
+                .with_span(func.name().get_span())
+                .with_label(body.span(), "some paths reach the end of this body without a `return`".to_string()));
+        }
+    }
+    fn validate_var_decl(&mut self, var: VariableDeclerationStatement) {
+        let Some(initializer) = var.initializer() else {
+            return;
+        };
+        self.validate_expr(initializer.clone());
+        let Some(SymbolKind::Variable(declared_type)) = self.get_local(self.current_scope, &var.name().get_data()) else {
+            return;
+        };
+        let initializer_type: TypeSpec = self.evaluate_expr_as(initializer.clone(), &declared_type);
+        if !is_assignable(&initializer_type, &declared_type) {
+            self.diag.emit(Diagnostic::new(DiagType::Error, format!("Mismatched types, variable `{}` is declared as `{}` but initialized with `{}`{}", var.name().get_data(), declared_type, initializer_type, conversion_hint(&initializer_type, &declared_type)))
+                .with_span(initializer.span())
+                .with_label(var.name().get_span(), format!("declared as `{}` here", declared_type)));
+        }
+    }
+    // Resolves names and checks calls and assignments, operator types are checked by `evaluate_expr_to_type`
     fn validate_expr(&mut self, expr: ExprType) {
         match expr {
-            ExprType::Identifier(identifier) => {
-                if !self.contains_name(self.current_scope.clone(), identifier.get_data()) {
-                    self.diag.print_formatted(DiagType::Error, format!("Use of undeclared identifier `{}`", identifier.get_data()));
+            ExprType::Identifier(identifier, id, span) => {
+                let name: String = identifier.get_data();
+                if let Some(definition) = self.lookup_visible_definition(&name, span.start) {
+                    self.resolutions.insert(id, definition);
+                } else {
+                    let declared_at: Option<Span> = self.lookup(self.current_scope, &name).and_then(|id| self.definitions[id].location);
+                    match declared_at {
+                        Some(declared_at) => {
+                            self.diag.emit(Diagnostic::new(DiagType::Error, format!("Use of variable `{}` before its declaration", name))
+                                .with_span(span)
+                                .with_label(declared_at, "declared here".to_string()));
+                        }
+                        None => {
+                            self.diag.print_formatted_at(DiagType::Error, span, format!("Use of undeclared identifier `{}`", name));
+                        }
+                    }
                 }
             }
-            ExprType::Call(callee, args) => {
-                // Box<ExprType>, Vec<ExprType>
-                self.validate_expr(*callee);
-                for arg in args {
+            ExprType::Unary(op, operand, _) => {
+                if op.get_type() == TokenType::Ampersand && !Self::is_place(&operand) {
+                    self.diag.print_formatted_at(DiagType::Error, operand.span(), "Cannot take the address of a temporary value".to_string());
+                }
+                self.validate_expr(*operand);
+            }
+            ExprType::Cast(value, _, _) => {
+                self.validate_expr(*value);
+            }
+            ExprType::Binary(left, _, right, _) => {
+                self.validate_expr(*left);
+                self.validate_expr(*right);
+            }
+            ExprType::Assign(target, op, value, _) => {
+                self.validate_expr(*target.clone());
+                self.validate_expr(*value.clone());
+                self.check_assignment(&target, &op, &value);
+            }
+            ExprType::NumericLiteral(_, _) | ExprType::FloatLiteral(_, _) | ExprType::StringLiteral(_, _) | ExprType::CharLiteral(_, _) | ExprType::NullLiteral(_, _) | ExprType::BoolLiteral(_, _) => {}
+            ExprType::Call(callee, args, span) => {
+                self.validate_expr(*callee.clone());
+                for arg in args.clone() {
                     self.validate_expr(arg);
                 }
+                self.check_call_arguments(&callee, &args, span);
+            }
+            ExprType::FieldAccess(base, _, _) => {
+                self.validate_expr(*base);
+            }
+            ExprType::StructLiteral(name, fields, span) => {
+                self.validate_expr(*name.clone());
+                self.check_struct_literal(&name, &fields, span);
             }
-            ExprType::MemberAccess(member, property) => {
+            ExprType::Match(match_expr) => {
+                self.check_match(&match_expr);
+            }
+            ExprType::MemberAccess(member, property, _) => {
+                // Resolves `a::b::c` one segment at a time, unknown prefixes were already reported
                 self.validate_expr(*member.clone());
-                let ExprType::Identifier(ref member_ident) = *member else { panic!("Improper parsing of memberExpr\n"); };
-                let ExprType::Identifier(ref property_ident) = *property else { panic!("Improper parsing of memberExpr\n"); };
-                let symbol: Option<SymbolKind> = self.get_symbol(member_ident.get_data());
-                if let Some(SymbolKind::Namespace(_)) = symbol {} else {
-                    self.diag.print_formatted(DiagType::Error, format!("Symbol `{}` isn't a structure or a namespace '{:?}'", member_ident.get_data(), symbol));
-                    exit(1);
+                let symbol: Option<SymbolKind> = self.resolve_path(&member);
+                let enum_key: Option<String> = symbol.as_ref().and_then(|symbol| self.enum_key(symbol));
+                let scope: Option<ScopeId> = match symbol {
+                    Some(SymbolKind::Namespace(scope)) => Some(scope),
+                    _ if enum_key.is_some() => None,
+                    Some(_) => {
+                        self.diag.print_formatted_at(DiagType::Error, member.span(), format!("`{}` isn't a namespace or an enum", Self::path_name(&member)));
+                        return;
+                    }
+                    None => return,
+                };
+                let ExprType::Identifier(ref property_ident, property_id, property_span) = *property else {
+                    self.diag.print_formatted_at(DiagType::Error, property.span(), "Expected a name after `::`".to_string());
+                    return;
+                };
+                let name: String = property_ident.get_data();
+                match (scope, enum_key) {
+                    (Some(scope), _) => match self.local(scope, &name) {
+                        Some(definition) => {
+                            self.resolutions.insert(property_id, definition);
+                        }
+                        None => {
+                            self.diag.print_formatted_at(DiagType::Error, property_span, format!("No member named `{}` in namespace `{}`", name, Self::path_name(&member)));
+                        }
+                    },
+                    (None, Some(key)) if !self.enums[&key].variants.iter().any(|variant| variant.name.get_data() == name) => {
+                        self.diag.print_formatted_at(DiagType::Error, property_span, format!("No variant named `{}` in enum `{}`", name, Self::path_name(&member)));
+                    }
+                    _ => {}
+                }
+            }
+            ExprType::Invalid(_) => {}
+        }
+    }
+    // Checks the patterns against the matched value, validates each arm with the names its pattern binds
+    // and records the type of the `match`: the common type of its arms, or `void` if one of them is a block
+    fn check_match(&mut self, match_expr: &MatchExpression) {
+        let scrutinee: ExprType = match_expr.scrutinee();
+        self.validate_expr(scrutinee.clone());
+        let scrutinee_type: TypeSpec = self.evaluate_expr_to_type(scrutinee.clone());
+        // `None` once a pattern is invalid, which would make exhaustiveness reports misleading
+        let mut rows: Option<Vec<(Pat, Span)>> = (scrutinee_type != TypeSpec::Invalid).then(Vec::new);
+        let mut arm_types: Vec<(TypeSpec, Span)> = vec![];
+        let mut literal_arms: Vec<ExprType> = vec![];
+        let mut has_block: bool = false;
+        for arm in match_expr.arms() {
+            let pattern: Pattern = arm.pattern();
+            let mut bindings: Vec<(Token, TypeSpec)> = vec![];
+            match (rows.as_mut(), self.check_pattern(&pattern, &scrutinee_type, &mut bindings)) {
+                (Some(rows), Some(pat)) => rows.push((pat, pattern.span())),
+                _ => rows = None,
+            }
+            if let Some(&arm_scope) = self.node_scopes.get(&arm.get_id()) {
+                for (name, type_spec) in bindings {
+                    self.define(arm_scope, name.get_data(), SymbolKind::Variable(type_spec), Some(name.get_span()));
+                }
+            }
+            self.enter_node_scope(arm.get_id());
+            match arm.body() {
+                StatementType::Expr(expr, _) if Self::is_unsuffixed_literal(&expr) => literal_arms.push(expr),
+                StatementType::Expr(expr, _) => {
+                    self.validate_expr(expr.clone());
+                    arm_types.push((self.evaluate_expr_to_type(expr.clone()), expr.span()));
                 }
-                if !self.contains_name(member_ident.get_data(), property_ident.get_data()) {
-                    self.diag.print_formatted(DiagType::Error, format!("No property named `{}` found in scope `{}`", property_ident.get_data(), member_ident.get_data()));
+                body => {
+                    has_block = true;
+                    self.validate_stmt(body);
                 }
             }
-            _ => {
-                self.diag.print_formatted(DiagType::Ice, format!("Handle validating expression `{:?}`", expr));
+            self.leave_scope();
+        }
+        // Like the operands of a binary operator, unsuffixed literals take the type of the other arms
+        let expected: Option<TypeSpec> = arm_types.iter().map(|(arm_type, _)| arm_type.clone()).find(|arm_type| *arm_type != TypeSpec::Invalid);
+        for literal in literal_arms {
+            let span: Span = literal.span();
+            let arm_type: TypeSpec = match &expected {
+                Some(expected) => self.evaluate_expr_as(literal, expected),
+                None => self.evaluate_expr_to_type(literal),
+            };
+            arm_types.push((arm_type, span));
+        }
+        arm_types.sort_by_key(|(_, span)| span.start);
+        let match_type: TypeSpec = if has_block || arm_types.is_empty() { TypeSpec::Void } else { self.common_arm_type(&arm_types) };
+        self.match_types.insert(match_expr.get_id(), match_type);
+        if let Some(rows) = rows && !self.check_exhaustiveness(&rows, &scrutinee_type, scrutinee.span()) {
+            self.non_exhaustive_matches.insert(match_expr.get_id());
+        }
+    }
+    // The type every arm converts to, arms that don't are reported
+    fn common_arm_type(&self, arm_types: &[(TypeSpec, Span)]) -> TypeSpec {
+        let mut common: Option<&(TypeSpec, Span)> = None;
+        for arm in arm_types.iter().filter(|(arm_type, _)| *arm_type != TypeSpec::Invalid) {
+            match common {
+                None => common = Some(arm),
+                Some((common_type, _)) if is_assignable(&arm.0, common_type) => {}
+                Some((common_type, _)) if is_assignable(common_type, &arm.0) => common = Some(arm),
+                Some((common_type, common_span)) => {
+                    self.diag.emit(Diagnostic::new(DiagType::Error, format!("Match arms have incompatible types, expected `{}` but this arm is `{}`{}", common_type, arm.0, conversion_hint(&arm.0, common_type)))
+                        .with_span(arm.1)
+                        .with_label(*common_span, format!("this arm is `{}`", common_type)));
+                }
             }
         }
+        common.map_or(TypeSpec::Invalid, |(common_type, _)| common_type.clone())
+    }
+    // Checks `pattern` against the type of the value it's matched with and collects the names it binds,
+    // returns the pattern reduced for exhaustiveness checking or `None` if it's invalid
+    fn check_pattern(&mut self, pattern: &Pattern, expected: &TypeSpec, bindings: &mut Vec<(Token, TypeSpec)>) -> Option<Pat> {
+        match pattern {
+            Pattern::Wildcard(_) => Some(Pat::Wild),
+            Pattern::Binding(name, _) => {
+                if let Some((previous, _)) = bindings.iter().find(|(previous, _)| previous.get_data() == name.get_data()) {
+                    self.diag.emit(Diagnostic::new(DiagType::Error, format!("`{}` is bound more than once in the same pattern", name.get_data()))
+                        .with_span(name.get_span())
+                        .with_label(previous.get_span(), "first bound here".to_string()));
+                    return None;
+                }
+                bindings.push((name.clone(), expected.clone()));
+                Some(Pat::Wild)
+            }
+            Pattern::Literal(literal, span) => {
+                let literal_type: TypeSpec = self.evaluate_expr_as(literal.clone(), expected);
+                if !is_assignable(&literal_type, expected) {
+                    self.diag.print_formatted_at(DiagType::Error, *span, format!("Mismatched types, the pattern is `{}` but the matched value is `{}`{}", literal_type, expected, conversion_hint(&literal_type, expected)));
+                    return None;
+                }
+                (literal_type != TypeSpec::Invalid).then(|| Pat::Constructor(Self::literal_constructor(literal), vec![]))
+            }
+            Pattern::Variant(path, fields, span) => {
+                self.validate_expr(path.clone());
+                let (key, index): (String, usize) = match self.resolve_path(path) {
+                    Some(SymbolKind::Variant(key, index)) => (key, index),
+                    Some(_) => {
+                        self.diag.print_formatted_at(DiagType::Error, path.span(), format!("`{}` is not an enum variant", Self::path_name(path)));
+                        return None;
+                    }
+                    // Unknown names were reported by `validate_expr`
+                    None => return None,
+                };
+                let field_types: Vec<TypeSpec> = self.enums[&key].variants[index].fields.clone();
+                let mut valid: bool = true;
+                if *expected != TypeSpec::Enum(key.clone()) && *expected != TypeSpec::Invalid {
+                    self.diag.print_formatted_at(DiagType::Error, *span, format!("Mismatched types, the pattern is a `{}` but the matched value is `{}`", key, expected));
+                    valid = false;
+                }
+                if field_types.len() != fields.len() {
+                    self.diag.print_formatted_at(DiagType::Error, *span, format!("Variant `{}` has {} field{} but the pattern has {}", Self::path_name(path), field_types.len(), if field_types.len() == 1 { "" } else { "s" }, fields.len()));
+                    valid = false;
+                }
+                // The fields are checked even if the variant is wrong so the names they bind are declared
+                let mut field_pats: Vec<Pat> = vec![];
+                for (field_index, field) in fields.iter().enumerate() {
+                    let field_type: TypeSpec = field_types.get(field_index).cloned().unwrap_or(TypeSpec::Invalid);
+                    match self.check_pattern(field, &field_type, bindings) {
+                        Some(field_pat) => field_pats.push(field_pat),
+                        None => valid = false,
+                    }
+                }
+                valid.then_some(Pat::Constructor(Constructor::Variant(index), field_pats))
+            }
+        }
+    }
+    // The value a literal pattern matches, so `0x10` and `16` are the same
+    fn literal_constructor(literal: &ExprType) -> Constructor {
+        match literal {
+            ExprType::BoolLiteral(token, _) => Constructor::Bool(token.get_type() == TokenType::True),
+            ExprType::NumericLiteral(token, _) => Constructor::Literal(integer_literal_value(&token.get_data()).0.map_or(token.get_data(), |value| value.to_string())),
+            ExprType::FloatLiteral(token, _) => Constructor::Literal(float_literal_value(&token.get_data()).0.map_or(token.get_data(), |value| format!("{:?}", value))),
+            ExprType::Unary(_, operand, _) => match Self::literal_constructor(operand) {
+                Constructor::Literal(value) if value.chars().any(|digit| !matches!(digit, '0' | '.')) => Constructor::Literal(format!("-{}", value)),
+                constructor => constructor,
+            },
+            ExprType::StringLiteral(token, _) | ExprType::CharLiteral(token, _) => Constructor::Literal(format!("{:?}", token.get_value())),
+            _ => Constructor::Literal(format!("{:?}", literal)),
+        }
+    }
+    // Every constructor of a type for exhaustiveness checking, `None` if there are too many to list
+    fn constructors(&self, type_spec: &TypeSpec) -> Option<Vec<(Constructor, Vec<TypeSpec>)>> {
+        match type_spec.canonical() {
+            TypeSpec::Enum(key) => Some(self.enums.get(&key)?.variants.iter().enumerate().map(|(index, variant)| (Constructor::Variant(index), variant.fields.clone())).collect()),
+            TypeSpec::Bool => Some(vec![(Constructor::Bool(true), vec![]), (Constructor::Bool(false), vec![])]),
+            _ => None,
+        }
+    }
+    // How a value found by `witness` is written as a pattern
+    fn render_pattern(&self, pat: &Pat, type_spec: &TypeSpec) -> String {
+        match (pat, type_spec.canonical()) {
+            (Pat::Constructor(Constructor::Variant(index), fields), TypeSpec::Enum(key)) => {
+                let variant: &Variant = &self.enums[&key].variants[*index];
+                if fields.is_empty() {
+                    return format!("{}::{}", key, variant.name.get_data());
+                }
+                let fields: Vec<String> = fields.iter().zip(&variant.fields).map(|(field, field_type)| self.render_pattern(field, field_type)).collect();
+                format!("{}::{}({})", key, variant.name.get_data(), fields.join(", "))
+            }
+            (Pat::Constructor(Constructor::Bool(value), _), _) => value.to_string(),
+            (Pat::Constructor(Constructor::Literal(value), _), _) => value.clone(),
+            _ => "_".to_string(),
+        }
+    }
+    // Warns about arms that earlier arms already cover and reports values no arm matches
+    // Reports unreachable arms and missing values, returns false if the match isn't exhaustive
+    fn check_exhaustiveness(&self, rows: &[(Pat, Span)], scrutinee_type: &TypeSpec, span: Span) -> bool {
+        let constructors = |type_spec: &TypeSpec| self.constructors(type_spec);
+        let types: [TypeSpec; 1] = [scrutinee_type.clone()];
+        let mut matrix: Vec<Vec<Pat>> = vec![];
+        for (pat, pattern_span) in rows {
+            let row: Vec<Pat> = vec![pat.clone()];
+            if witness(&matrix, &row, &types, &constructors).is_none() {
+                self.diag.print_formatted_at(DiagType::Warning, *pattern_span, "Unreachable match arm, the arms before it already match every value it does".to_string());
+            }
+            matrix.push(row);
+        }
+        // Every missing variant is listed, values of other types can only be covered by `_`
+        let candidates: Vec<Pat> = match self.constructors(scrutinee_type) {
+            Some(all) => all.into_iter().map(|(constructor, field_types)| Pat::Constructor(constructor, vec![Pat::Wild; field_types.len()])).collect(),
+            None => vec![Pat::Wild],
+        };
+        let missing: Vec<String> = candidates.into_iter()
+            .filter_map(|candidate| witness(&matrix, &[candidate], &types, &constructors))
+            .map(|values| format!("`{}`", self.render_pattern(&values[0], scrutinee_type)))
+            .collect();
+        if missing == ["`_`"] {
+            self.diag.print_formatted_at(DiagType::Error, span, format!("Non-exhaustive match, not every value of type `{}` is covered, add a `_` arm", scrutinee_type));
+        } else if !missing.is_empty() {
+            self.diag.print_formatted_at(DiagType::Error, span, format!("Non-exhaustive match on `{}`, missing {}", scrutinee_type, missing.join(", ")));
+        }
+        missing.is_empty()
+    }
+    fn check_condition(&mut self, condition: ExprType) {
+        self.validate_expr(condition.clone());
+        let condition_type: TypeSpec = self.evaluate_expr_to_type(condition.clone());
+        if !is_condition_type(&condition_type) && condition_type != TypeSpec::Invalid {
+            self.diag.print_formatted_at(DiagType::Error, condition.span(), format!("Condition must have a boolean-compatible type, but has type `{}`", condition_type));
+        }
+    }
+    fn validate_for(&mut self, for_stmt: ForStatement) {
+        let iterable: ExprType = for_stmt.iterable();
+        self.validate_expr(iterable.clone());
+        let iterable_type: TypeSpec = self.evaluate_expr_to_type(iterable.clone());
+        let Some(&for_scope) = self.node_scopes.get(&for_stmt.get_id()) else {
+            return;
+        };
+        let Some(SymbolKind::Variable(variable_type)) = self.get_local(for_scope, &for_stmt.variable().get_data()) else {
+            return;
+        };
+        match element_type(&iterable_type) {
+            Some(element) if element != variable_type && variable_type != TypeSpec::Invalid => {
+                self.diag.emit(Diagnostic::new(DiagType::Error, format!("Mismatched types, loop variable `{}` is declared as `{}` but the elements are `{}`", for_stmt.variable().get_data(), variable_type, element))
+                    .with_span(for_stmt.variable().get_span())
+                    .with_label(iterable.span(), format!("this iterates over `{}`", element)));
+            }
+            None if iterable_type != TypeSpec::Invalid => {
+                self.diag.print_formatted_at(DiagType::Error, iterable.span(), format!("Type `{}` can't be iterated over", iterable_type));
+            }
+            _ => {}
+        }
+        self.enter_scope(for_scope);
+        self.validate_stmt(for_stmt.body());
+        self.leave_scope();
     }
     fn validate_stmt(&mut self, stmt: StatementType) {
         match stmt {
@@ -223,31 +1674,75 @@ impl SemaChecker {
                 self.validate_func(*func.clone());
             }
             StatementType::Block(block) => {
+                self.enter_node_scope(block.get_id());
                 for stmt in block.body {
                     self.validate_stmt(stmt);
                 }
+                self.leave_scope();
             }
-            StatementType::Expr(expr) => {
-                self.validate_expr(expr);
+            StatementType::Decleration(DeclerationType::Variable(var)) => {
+                self.validate_var_decl(*var);
             }
-            _ => {
-                self.diag.print_formatted(DiagType::Ice, format!("Handle validating for statement `{:?}`", stmt));
+            StatementType::Decleration(DeclerationType::Namespace(namespace)) => {
+                // A namespace conflicting with another declaration was reported by `declare_namespace`
+                let Some(SymbolKind::Namespace(scope)) = self.get_local(self.current_scope, &namespace.name().get_data()) else {
+                    return;
+                };
+                self.enter_scope(scope);
+                for stmt in namespace.body() {
+                    self.validate_stmt(stmt);
+                }
+                self.leave_scope();
+            }
+            // Field types were resolved and laid out during the first pass, and aliases before that
+            StatementType::Decleration(DeclerationType::Struct(_) | DeclerationType::TypeAlias(_) | DeclerationType::Enum(_)) => {}
+            StatementType::Expr(expr, _) => {
+                self.validate_expr(expr.clone());
+                self.evaluate_expr_to_type(expr);
+            }
+            StatementType::Return(expr, span) => {
+                self.check_return(expr, span);
+            }
+            StatementType::If(if_stmt) => {
+                self.check_condition(if_stmt.condition());
+                self.validate_stmt(if_stmt.then_branch());
+                if let Some(else_branch) = if_stmt.else_branch() {
+                    self.validate_stmt(else_branch);
+                }
+            }
+            StatementType::While(while_stmt) => {
+                self.check_condition(while_stmt.condition());
+                self.validate_stmt(while_stmt.body());
             }
+            StatementType::For(for_stmt) => {
+                self.validate_for(*for_stmt);
+            }
+            StatementType::Invalid(_) | StatementType::Decleration(DeclerationType::Invalid(_)) => {}
         }
     }
     fn second_pass(&mut self) {
         for stmt in self.ast.body.clone() {
+            if self.diag.limit_reached() {
+                break;
+            }
             self.validate_stmt(stmt);
         }
     }
     pub fn check(&mut self) {
-        println!("Sema init");
+        let verbose: bool = self.diag.is_verbose();
+        if verbose {
+            println!("Sema init");
+        }
         self.init();
-        println!("Symbols: {:#?}", self.scopes);
-        println!("Sema First pass");
+        if verbose {
+            println!("Symbols: {:#?}", self.scopes);
+            println!("Sema First pass");
+        }
         self.first_pass();
-        println!("Symbols: {:#?}", self.scopes);
-        println!("Sema Second pass");
+        if verbose {
+            println!("Symbols: {:#?}", self.scopes);
+            println!("Sema Second pass");
+        }
         self.second_pass();
     }
 }
\ No newline at end of file
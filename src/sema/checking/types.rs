@@ -0,0 +1,146 @@
+use crate::syntax::{ast::TypeSpec, token::TokenType};
+
+// Builtin integer types with the range of values they hold, the name is also the literal suffix
+pub const INTEGER_TYPES: &[(&str, TypeSpec, i128, i128)] = &[
+    ("int", TypeSpec::Int, i64::MIN as i128, i64::MAX as i128),
+    ("i8", TypeSpec::I8, i8::MIN as i128, i8::MAX as i128),
+    ("i16", TypeSpec::I16, i16::MIN as i128, i16::MAX as i128),
+    ("i32", TypeSpec::I32, i32::MIN as i128, i32::MAX as i128),
+    ("i64", TypeSpec::I64, i64::MIN as i128, i64::MAX as i128),
+    ("u8", TypeSpec::U8, 0, u8::MAX as i128),
+    ("u16", TypeSpec::U16, 0, u16::MAX as i128),
+    ("u32", TypeSpec::U32, 0, u32::MAX as i128),
+    ("u64", TypeSpec::U64, 0, u64::MAX as i128),
+];
+
+pub fn integer_range(type_spec: &TypeSpec) -> Option<(i128, i128)> {
+    INTEGER_TYPES.iter().find(|(_, integer, _, _)| integer == type_spec).map(|(_, _, min, max)| (*min, *max))
+}
+
+pub fn is_integer(type_spec: &TypeSpec) -> bool {
+    integer_range(type_spec).is_some()
+}
+
+pub fn is_float(type_spec: &TypeSpec) -> bool {
+    matches!(type_spec.canonical(), TypeSpec::F32 | TypeSpec::F64)
+}
+
+pub fn is_numeric(type_spec: &TypeSpec) -> bool {
+    is_integer(type_spec) || is_float(type_spec)
+}
+
+// Whether every value of the numeric type `from` is also a value of `to`,
+// integers never widen to floats since large ones would lose precision
+fn widens(from: &TypeSpec, to: &TypeSpec) -> bool {
+    match (integer_range(from), integer_range(to)) {
+        (Some((from_min, from_max)), Some((to_min, to_max))) => to_min <= from_min && from_max <= to_max,
+        _ => matches!((from.canonical(), to.canonical()), (TypeSpec::F32, TypeSpec::F64)),
+    }
+}
+
+// Types that can be used as the condition of an `if` or `while`, or with `!`, `&&` and `||`
+pub fn is_condition_type(type_spec: &TypeSpec) -> bool {
+    *type_spec == TypeSpec::Bool || is_integer(type_spec)
+}
+
+// Whether a value of type `from` can be stored where `to` is expected without an `as`,
+// invalid types were already reported so they are compatible with everything
+pub fn is_assignable(from: &TypeSpec, to: &TypeSpec) -> bool {
+    if *from == TypeSpec::Invalid || *to == TypeSpec::Invalid || from == to {
+        return true;
+    }
+    widens(from, to) || matches!((from.canonical(), to.canonical()), (TypeSpec::Null, TypeSpec::Pointer(_)))
+}
+
+// The legal `as` conversions besides the implicit ones:
+//  integer -> integer    truncates or extends, and may change the sign
+//  float  <-> integer    rounds toward zero, saturating at the integer's limits
+//  float   -> float      rounds to the nearest `f32`
+//  bool    -> integer    `false` is 0 and `true` is 1
+//  char   <-> integer    the character's code
+//  pointer <-> 64 bit integer
+//  pointer -> pointer
+// Everything else, like `"abc" as int` or `1 as bool`, is rejected
+pub fn is_valid_cast(from: &TypeSpec, to: &TypeSpec) -> bool {
+    let pointer_sized = |type_spec: &TypeSpec| matches!(type_spec, TypeSpec::Int | TypeSpec::I64 | TypeSpec::U64);
+    if is_assignable(from, to) || (is_numeric(from) && is_numeric(to)) {
+        return true;
+    }
+    match (&from.canonical(), &to.canonical()) {
+        (TypeSpec::Bool, to) => is_integer(to),
+        (TypeSpec::Char, other) | (other, TypeSpec::Char) => is_integer(other),
+        (TypeSpec::Pointer(_), other) | (other, TypeSpec::Pointer(_)) => pointer_sized(other) || matches!(other, TypeSpec::Pointer(_)),
+        _ => false,
+    }
+}
+
+// Appended to the error for an illegal or missing conversion to point at the right fix
+pub fn conversion_hint(from: &TypeSpec, to: &TypeSpec) -> &'static str {
+    match (&from.canonical(), &to.canonical()) {
+        (TypeSpec::Pointer(_) | TypeSpec::Null, other) | (other, TypeSpec::Pointer(_) | TypeSpec::Null) if is_integer(other) => ", pointers and integers need an explicit `as` to convert",
+        (from, TypeSpec::Bool) if is_integer(from) => ", compare with `0` instead",
+        (from, to) if is_integer(from) && is_integer(to) => ", narrowing or changing the sign needs an explicit `as`",
+        (TypeSpec::F64, TypeSpec::F32) => ", narrowing needs an explicit `as`",
+        (from, to) if is_numeric(from) && is_numeric(to) && is_float(from) != is_float(to) => ", integers and floats need an explicit `as` to convert",
+        _ => "",
+    }
+}
+
+// Result of a binary operator on two valid operand types, `None` if the operator doesn't apply.
+// Integers or floats of different types are widened to the larger one, comparisons produce `bool` and
+// pointers can be offset by integers, subtracted from pointers of the same type and compared
+pub fn binary_type(op: TokenType, left: &TypeSpec, right: &TypeSpec) -> Option<TypeSpec> {
+    let (left, right): (&TypeSpec, &TypeSpec) = (&left.canonical(), &right.canonical());
+    let equality: bool = matches!(op, TokenType::EqualEqual | TokenType::BangEqual);
+    let comparison: bool = equality || matches!(op, TokenType::Less | TokenType::LessEqual | TokenType::Greater | TokenType::GreaterEqual);
+    let logical: bool = matches!(op, TokenType::AmpersandAmpersand | TokenType::PipePipe);
+    let bitwise: bool = matches!(op, TokenType::Ampersand | TokenType::Pipe | TokenType::Caret);
+    let shift: bool = matches!(op, TokenType::LessLess | TokenType::GreaterGreater);
+    match (left, right) {
+        (TypeSpec::Pointer(_), offset) if is_integer(offset) && matches!(op, TokenType::Plus | TokenType::Minus) => Some(left.clone()),
+        (offset, TypeSpec::Pointer(_)) if is_integer(offset) && op == TokenType::Plus => Some(right.clone()),
+        (TypeSpec::Pointer(_), TypeSpec::Pointer(_)) if left == right && op == TokenType::Minus => Some(TypeSpec::Int),
+        (TypeSpec::Pointer(_), TypeSpec::Pointer(_)) if left == right && comparison => Some(TypeSpec::Bool),
+        (TypeSpec::Pointer(_) | TypeSpec::Null, TypeSpec::Pointer(_) | TypeSpec::Null) if equality => Some(TypeSpec::Bool),
+        (TypeSpec::Pointer(_) | TypeSpec::Null, _) | (_, TypeSpec::Pointer(_) | TypeSpec::Null) => None,
+        _ if logical => (is_condition_type(left) && is_condition_type(right)).then_some(TypeSpec::Bool),
+        (TypeSpec::Bool, TypeSpec::Bool) if equality || bitwise => Some(TypeSpec::Bool),
+        _ if shift => (is_integer(left) && is_integer(right)).then(|| left.clone()),
+        _ if (is_integer(left) && is_integer(right)) || (is_float(left) && is_float(right) && !bitwise) => {
+            let common: TypeSpec = if left == right || widens(left, right) { right.clone() } else if widens(right, left) { left.clone() } else { return None };
+            Some(if comparison { TypeSpec::Bool } else { common })
+        }
+        (TypeSpec::Char, TypeSpec::Char) if comparison => Some(TypeSpec::Bool),
+        (TypeSpec::String, TypeSpec::String) if equality => Some(TypeSpec::Bool),
+        (TypeSpec::String, TypeSpec::String) if op == TokenType::Plus => Some(TypeSpec::String),
+        _ => None,
+    }
+}
+
+// Appended to the error for a rejected `as` to point at the right fix
+pub fn cast_hint(from: &TypeSpec, to: &TypeSpec) -> &'static str {
+    match (&from.canonical(), &to.canonical()) {
+        (from, TypeSpec::Bool) if is_integer(from) => ", compare with `0` instead",
+        (TypeSpec::Pointer(_), other) | (other, TypeSpec::Pointer(_)) if is_integer(other) => ", only 64 bit integers can hold a pointer",
+        _ => "",
+    }
+}
+
+// `type_spec` from a generic struct's declaration with its type parameters replaced by `args`
+pub fn substitute(type_spec: &TypeSpec, params: &[String], args: &[TypeSpec]) -> TypeSpec {
+    match type_spec {
+        TypeSpec::Parameter(name) => params.iter().position(|param| param == name).and_then(|index| args.get(index)).cloned().unwrap_or(TypeSpec::Invalid),
+        TypeSpec::Pointer(pointee) => TypeSpec::Pointer(Box::new(substitute(pointee, params, args))),
+        TypeSpec::Instance(key, instance_args) => TypeSpec::Instance(key.clone(), instance_args.iter().map(|arg| substitute(arg, params, args)).collect()),
+        _ => type_spec.clone(),
+    }
+}
+
+// How deeply generic instances are nested in `type_spec`, `Array<Array<int>>*` is 2
+pub fn instance_depth(type_spec: &TypeSpec) -> usize {
+    match type_spec {
+        TypeSpec::Pointer(pointee) => instance_depth(pointee),
+        TypeSpec::Instance(_, args) => 1 + args.iter().map(instance_depth).max().unwrap_or(0),
+        _ => 0,
+    }
+}
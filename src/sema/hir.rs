@@ -0,0 +1,78 @@
+use crate::syntax::{ast::TypeSpec, span::Span, token::TokenType};
+use super::checking::{layout::Layout, sema::{Definition, DefinitionId, EnumDefinition, StructDefinition}};
+use std::collections::HashMap;
+
+// The typed program sema produces for the phases after it. Every expression has its resolved type,
+// names are the definitions they refer to and implicit conversions are explicit `Convert` nodes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hir {
+    pub functions: Vec<Function>, // Including the ones in namespaces
+    pub definitions: Vec<Definition>, // What each `DefinitionId` refers to
+    pub structs: HashMap<String, StructDefinition>, // Keyed like the types that name them
+    pub enums: HashMap<String, EnumDefinition>,
+    pub layouts: HashMap<String, Option<Layout>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+    pub name: String, // Qualified with the namespaces it's declared in, `a::f`
+    pub definition: DefinitionId,
+    pub params: Vec<DefinitionId>,
+    pub return_type: TypeSpec,
+    pub body: Option<Stmt>, // `None` for `func external` declarations
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stmt {
+    Let(DefinitionId, Option<Expr>, Span), // `var`, the initializer already has the variable's type
+    Expr(Expr, Span),
+    Block(Vec<Stmt>, Span),
+    Return(Option<Expr>, Span),
+    If(Expr, Box<Stmt>, Option<Box<Stmt>>, Span), // Conditions are always `bool`
+    While(Expr, Box<Stmt>, Span),
+    For(DefinitionId, Expr, Box<Stmt>, Span), // The loop variable, what it iterates over and the body
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub type_spec: TypeSpec,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExprKind {
+    Integer(i128),
+    Float(f64),
+    String(String),
+    Char(char),
+    Bool(bool),
+    Null,
+    Variable(DefinitionId),
+    Unary(TokenType, Box<Expr>), // `-`, `!`, `~`, `*` and `&`
+    Binary(Box<Expr>, TokenType, Box<Expr>), // Both operands have the same type, except for shifts and pointer offsets
+    Assign(Box<Expr>, TokenType, Box<Expr>), // `=` and the compound assignments, the value has the target's type
+    Field(Box<Expr>, usize), // Index of the field in the struct's definition
+    StructLiteral(Vec<(usize, Expr)>), // Field indices and values in the order they were written
+    Call(DefinitionId, Vec<Expr>), // Arguments already have the parameters' types
+    Variant(String, usize, Vec<Expr>), // Key of the enum, index of the variant and its fields
+    Cast(Box<Expr>), // An `as` to the expression's type
+    Convert(Box<Expr>), // An implicit conversion to the expression's type: widening, `null` to a pointer, or an integer condition to `bool`
+    Match(Box<Expr>, Vec<Arm>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arm {
+    pub pattern: Pattern,
+    pub body: Stmt, // A `Stmt::Expr` producing the match's value, or a block for a `void` match
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    Wildcard,
+    Binding(DefinitionId),
+    Literal(Expr),
+    Variant(String, usize, Vec<Pattern>), // Key of the enum, index of the variant and a pattern for each field
+}
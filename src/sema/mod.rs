@@ -1,2 +1,3 @@
 pub mod analyzer;
-pub mod checking;
\ No newline at end of file
+pub mod checking;
+pub mod hir;
@@ -1,4 +1,5 @@
-use super::token::Token;
+use super::{span::Span, token::Token};
+use std::fmt;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Ast {
@@ -7,52 +8,291 @@ pub struct Ast {
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum StatementType {
-    Invalid,
+    Invalid(Span),
     Decleration(DeclerationType),
-    Expr(ExprType),
+    Expr(ExprType, Span),
     Block(BlockStatement),
-    Return(ExprType),
+    Return(Option<ExprType>, Span), // `None` for a bare `return;`
+    If(Box<IfStatement>),
+    While(Box<WhileStatement>),
+    For(Box<ForStatement>),
 }
 
-#[derive(Debug, Clone, PartialEq, Copy)]
+// Equality looks through aliases, see the `PartialEq` impl
+#[derive(Debug, Clone)]
 pub enum TypeSpec {
     Invalid,
-    Int,
+    Int, // Signed 64 bit, the type of integer literals without a suffix
     String,
-    // Alias(TypeSpec),
+    Char,
+    Void, // Only valid as a return type
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+    Bool,
+    F32,
+    F64, // The type of float literals without a suffix
+    Named(String), // A user defined type as written, e.g. `geometry::Point`, resolved by the checker
+    Generic(String, Vec<TypeSpec>), // A generic type with its arguments as written, e.g. `std::Array<int>`
+    Struct(String), // A resolved struct, the key of its definition in the checker
+    Enum(String), // A resolved enum, the key of its definition in the checker
+    Instance(String, Vec<TypeSpec>), // A resolved generic struct and its arguments, the key of the template
+    Parameter(String), // A type parameter inside the generic struct declaring it
+    Pointer(Box<TypeSpec>),
+    Null, // Type of the `null` literal, converts to any pointer
+    Alias(String, Box<TypeSpec>), // A resolved type alias, its name and the type it stands for
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct BlockStatement {
     pub body: Vec<StatementType>,
     id: usize,
+    span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IfStatement {
+    condition: ExprType,
+    then_branch: StatementType,
+    else_branch: Option<StatementType>, // Either a block or another `if` for `else if`
+    span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WhileStatement {
+    condition: ExprType,
+    body: StatementType,
+    span: Span,
+}
+
+// `for (variable: type in iterable) body`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForStatement {
+    variable: Token,
+    type_spec: TypeSpec,
+    iterable: ExprType,
+    body: StatementType,
+    id: usize,
+    span: Span,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 #[repr(u64)]
 pub enum DeclerationType {
-    Invalid,
+    Invalid(Span),
     Function(Box<FunctionDeclerationStatement>),
+    Variable(Box<VariableDeclerationStatement>),
+    Namespace(Box<NamespaceDeclerationStatement>),
+    Struct(Box<StructDeclerationStatement>),
+    TypeAlias(Box<TypeAliasDeclerationStatement>),
+    Enum(Box<EnumDeclerationStatement>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parameter {
+    pub name: Token,
+    pub type_spec: TypeSpec,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub name: Token,
+    pub type_spec: TypeSpec,
+    pub span: Span,
+}
+
+// A variant of an enum, `Name` or `Name(type, ...)` for one that carries data
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variant {
+    pub name: Token,
+    pub fields: Vec<TypeSpec>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldInitializer {
+    pub name: Token,
+    pub value: ExprType,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct FunctionDeclerationStatement {
     name: Token,
+    params: Vec<Parameter>,
     return_type: TypeSpec,
-    body: StatementType,
+    body: Option<StatementType>, // `None` for `func external` declarations
+    external: bool,
+    id: usize,
+    span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamespaceDeclerationStatement {
+    name: Token,
+    body: Vec<StatementType>,
+    span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructDeclerationStatement {
+    name: Token,
+    type_params: Vec<Token>, // Empty unless the struct is generic
+    fields: Vec<Field>,
+    span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumDeclerationStatement {
+    name: Token,
+    variants: Vec<Variant>,
+    span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeAliasDeclerationStatement {
+    name: Token,
+    type_spec: TypeSpec,
+    span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariableDeclerationStatement {
+    name: Token,
+    type_spec: TypeSpec,
+    initializer: Option<ExprType>,
+    span: Span,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 #[repr(u64)]
 pub enum ExprType {
-    Invalid,
-    Identifier(Token),
-    StringLiteral(Token),
-    NumericLiteral(Token),
-    Binary(Box<ExprType>, Token, Box<ExprType>),
-    MemberAccess(Box<ExprType>, Box<ExprType>),
-    Call(Box<ExprType>, Vec<ExprType>),
-    Cast(Box<ExprType>, TypeSpec),
+    Invalid(Span),
+    Identifier(Token, usize, Span), // The id keys what the name was resolved to
+    StringLiteral(Token, Span),
+    NumericLiteral(Token, Span),
+    FloatLiteral(Token, Span),
+    NullLiteral(Token, Span),
+    CharLiteral(Token, Span),
+    BoolLiteral(Token, Span),
+    Unary(Token, Box<ExprType>, Span),
+    Binary(Box<ExprType>, Token, Box<ExprType>, Span),
+    Assign(Box<ExprType>, Token, Box<ExprType>, Span), // `=` and the compound assignments
+    MemberAccess(Box<ExprType>, Box<ExprType>, Span),
+    FieldAccess(Box<ExprType>, Token, Span), // `value.field`
+    StructLiteral(Box<ExprType>, Vec<FieldInitializer>, Span), // `Name { field: value, ... }`
+    Call(Box<ExprType>, Vec<ExprType>, Span),
+    Cast(Box<ExprType>, TypeSpec, Span),
+    Match(Box<MatchExpression>),
+}
+
+// `match scrutinee { pattern => body, ... }`, also used as a statement
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchExpression {
+    scrutinee: ExprType,
+    arms: Vec<MatchArm>,
+    id: usize,
+    span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchArm {
+    pattern: Pattern,
+    body: StatementType, // A block, or an `Expr` statement for `pattern => expression`
+    id: usize, // Names the scope holding the pattern's bindings
+    span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    Wildcard(Span), // `_`
+    Binding(Token, Span), // A name, binds the matched value
+    Literal(ExprType, Span), // A literal, numbers may be negated
+    Variant(ExprType, Vec<Pattern>, Span), // `Enum::Variant`, with a pattern for each field if it carries data
+}
+
+// An alias is the same type as the one it stands for
+impl PartialEq for TypeSpec {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (TypeSpec::Alias(_, aliased), other) | (other, TypeSpec::Alias(_, aliased)) => **aliased == *other,
+            (TypeSpec::Named(first), TypeSpec::Named(second)) |
+            (TypeSpec::Struct(first), TypeSpec::Struct(second)) |
+            (TypeSpec::Enum(first), TypeSpec::Enum(second)) |
+            (TypeSpec::Parameter(first), TypeSpec::Parameter(second)) => first == second,
+            (TypeSpec::Generic(first, first_args), TypeSpec::Generic(second, second_args)) |
+            (TypeSpec::Instance(first, first_args), TypeSpec::Instance(second, second_args)) => first == second && first_args == second_args,
+            (TypeSpec::Pointer(first), TypeSpec::Pointer(second)) => first == second,
+            // Every other variant carries no data
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+}
+
+impl TypeSpec {
+    // The type with every alias in it replaced by what it stands for,
+    // the checker matches on this so aliases behave like the type itself
+    pub fn canonical(&self) -> TypeSpec {
+        match self {
+            TypeSpec::Alias(_, aliased) => aliased.canonical(),
+            TypeSpec::Pointer(pointee) => TypeSpec::Pointer(Box::new(pointee.canonical())),
+            TypeSpec::Generic(name, args) => TypeSpec::Generic(name.clone(), args.iter().map(TypeSpec::canonical).collect()),
+            TypeSpec::Instance(name, args) => TypeSpec::Instance(name.clone(), args.iter().map(TypeSpec::canonical).collect()),
+            _ => self.clone(),
+        }
+    }
+    fn contains_alias(&self) -> bool {
+        match self {
+            TypeSpec::Alias(..) => true,
+            TypeSpec::Pointer(pointee) => pointee.contains_alias(),
+            TypeSpec::Generic(_, args) | TypeSpec::Instance(_, args) => args.iter().any(TypeSpec::contains_alias),
+            _ => false,
+        }
+    }
+    // How the type is spelled in source, aliases by their name
+    fn spelling(&self) -> String {
+        match self {
+            TypeSpec::Invalid => "<invalid>".to_string(),
+            TypeSpec::Int => "int".to_string(),
+            TypeSpec::String => "string".to_string(),
+            TypeSpec::Char => "char".to_string(),
+            TypeSpec::Void => "void".to_string(),
+            TypeSpec::I8 => "i8".to_string(),
+            TypeSpec::I16 => "i16".to_string(),
+            TypeSpec::I32 => "i32".to_string(),
+            TypeSpec::I64 => "i64".to_string(),
+            TypeSpec::U8 => "u8".to_string(),
+            TypeSpec::U16 => "u16".to_string(),
+            TypeSpec::U32 => "u32".to_string(),
+            TypeSpec::U64 => "u64".to_string(),
+            TypeSpec::Bool => "bool".to_string(),
+            TypeSpec::F32 => "f32".to_string(),
+            TypeSpec::F64 => "f64".to_string(),
+            TypeSpec::Named(name) | TypeSpec::Struct(name) | TypeSpec::Enum(name) | TypeSpec::Parameter(name) | TypeSpec::Alias(name, _) => name.clone(),
+            TypeSpec::Generic(name, args) | TypeSpec::Instance(name, args) => {
+                let args: Vec<String> = args.iter().map(TypeSpec::spelling).collect();
+                format!("{}<{}>", name, args.join(", "))
+            }
+            TypeSpec::Pointer(pointee) => format!("{}*", pointee.spelling()),
+            TypeSpec::Null => "null".to_string(),
+        }
+    }
+}
+
+// Used by diagnostics, types written with an alias also show what it stands for: `Meters* (aka int*)`
+impl fmt::Display for TypeSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.contains_alias() {
+            return write!(f, "{} (aka {})", self.spelling(), self.canonical().spelling());
+        }
+        write!(f, "{}", self.spelling())
+    }
 }
 
 impl Ast {
@@ -66,19 +306,168 @@ impl Ast {
     }
 }
 
+impl StatementType {
+    pub fn span(&self) -> Span {
+        match self {
+            StatementType::Invalid(span) |
+            StatementType::Expr(_, span) |
+            StatementType::Return(_, span) => *span,
+            StatementType::Decleration(decl) => decl.span(),
+            StatementType::Block(block) => block.span(),
+            StatementType::If(if_stmt) => if_stmt.span(),
+            StatementType::While(while_stmt) => while_stmt.span(),
+            StatementType::For(for_stmt) => for_stmt.span(),
+        }
+    }
+}
+
+impl DeclerationType {
+    pub fn span(&self) -> Span {
+        match self {
+            DeclerationType::Invalid(span) => *span,
+            DeclerationType::Function(func) => func.span(),
+            DeclerationType::Variable(var) => var.span(),
+            DeclerationType::Namespace(namespace) => namespace.span(),
+            DeclerationType::Struct(struct_decl) => struct_decl.span(),
+            DeclerationType::TypeAlias(alias) => alias.span(),
+            DeclerationType::Enum(enum_decl) => enum_decl.span(),
+        }
+    }
+}
+
+impl ExprType {
+    pub fn identifier(name: Token, span: Span) -> Self {
+        ExprType::Identifier(name, gen_new_id(), span)
+    }
+    pub fn span(&self) -> Span {
+        match self {
+            ExprType::Invalid(span) |
+            ExprType::Identifier(_, _, span) |
+            ExprType::StringLiteral(_, span) |
+            ExprType::NumericLiteral(_, span) |
+            ExprType::FloatLiteral(_, span) |
+            ExprType::NullLiteral(_, span) |
+            ExprType::CharLiteral(_, span) |
+            ExprType::BoolLiteral(_, span) |
+            ExprType::Unary(_, _, span) |
+            ExprType::Binary(_, _, _, span) |
+            ExprType::Assign(_, _, _, span) |
+            ExprType::MemberAccess(_, _, span) |
+            ExprType::FieldAccess(_, _, span) |
+            ExprType::StructLiteral(_, _, span) |
+            ExprType::Call(_, _, span) |
+            ExprType::Cast(_, _, span) => *span,
+            ExprType::Match(match_expr) => match_expr.span(),
+        }
+    }
+}
+
 impl FunctionDeclerationStatement {
-    pub fn new(name: Token, return_type: TypeSpec, body: StatementType) -> Self {
-        Self { name, return_type, body }
+    pub fn new(name: Token, params: Vec<Parameter>, return_type: TypeSpec, body: Option<StatementType>, external: bool, span: Span) -> Self {
+        Self { name, params, return_type, body, external, id: gen_new_id(), span }
     }
     pub fn name(&self) -> Token {
         self.name.clone()
     }
+    pub fn params(&self) -> Vec<Parameter> {
+        self.params.clone()
+    }
     pub fn return_type(&self) -> TypeSpec {
-        self.return_type
+        self.return_type.clone()
     }
-    pub fn body(&self) -> StatementType {
+    pub fn body(&self) -> Option<StatementType> {
+        self.body.clone()
+    }
+    pub fn is_external(&self) -> bool {
+        self.external
+    }
+    pub fn get_id(&self) -> usize {
+        self.id
+    }
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl NamespaceDeclerationStatement {
+    pub fn new(name: Token, body: Vec<StatementType>, span: Span) -> Self {
+        Self { name, body, span }
+    }
+    pub fn name(&self) -> Token {
+        self.name.clone()
+    }
+    pub fn body(&self) -> Vec<StatementType> {
         self.body.clone()
     }
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl StructDeclerationStatement {
+    pub fn new(name: Token, type_params: Vec<Token>, fields: Vec<Field>, span: Span) -> Self {
+        Self { name, type_params, fields, span }
+    }
+    pub fn name(&self) -> Token {
+        self.name.clone()
+    }
+    pub fn type_params(&self) -> Vec<Token> {
+        self.type_params.clone()
+    }
+    pub fn fields(&self) -> Vec<Field> {
+        self.fields.clone()
+    }
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl EnumDeclerationStatement {
+    pub fn new(name: Token, variants: Vec<Variant>, span: Span) -> Self {
+        Self { name, variants, span }
+    }
+    pub fn name(&self) -> Token {
+        self.name.clone()
+    }
+    pub fn variants(&self) -> Vec<Variant> {
+        self.variants.clone()
+    }
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl TypeAliasDeclerationStatement {
+    pub fn new(name: Token, type_spec: TypeSpec, span: Span) -> Self {
+        Self { name, type_spec, span }
+    }
+    pub fn name(&self) -> Token {
+        self.name.clone()
+    }
+    pub fn type_spec(&self) -> TypeSpec {
+        self.type_spec.clone()
+    }
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl VariableDeclerationStatement {
+    pub fn new(name: Token, type_spec: TypeSpec, initializer: Option<ExprType>, span: Span) -> Self {
+        Self { name, type_spec, initializer, span }
+    }
+    pub fn name(&self) -> Token {
+        self.name.clone()
+    }
+    pub fn type_spec(&self) -> TypeSpec {
+        self.type_spec.clone()
+    }
+    pub fn initializer(&self) -> Option<ExprType> {
+        self.initializer.clone()
+    }
+    pub fn span(&self) -> Span {
+        self.span
+    }
 }
 
 static mut BLOCKS: usize = 0;
@@ -90,11 +479,117 @@ fn gen_new_id() -> usize {
     }
 }
 
+impl IfStatement {
+    pub fn new(condition: ExprType, then_branch: StatementType, else_branch: Option<StatementType>, span: Span) -> Self {
+        Self { condition, then_branch, else_branch, span }
+    }
+    pub fn condition(&self) -> ExprType {
+        self.condition.clone()
+    }
+    pub fn then_branch(&self) -> StatementType {
+        self.then_branch.clone()
+    }
+    pub fn else_branch(&self) -> Option<StatementType> {
+        self.else_branch.clone()
+    }
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl WhileStatement {
+    pub fn new(condition: ExprType, body: StatementType, span: Span) -> Self {
+        Self { condition, body, span }
+    }
+    pub fn condition(&self) -> ExprType {
+        self.condition.clone()
+    }
+    pub fn body(&self) -> StatementType {
+        self.body.clone()
+    }
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl ForStatement {
+    pub fn new(variable: Token, type_spec: TypeSpec, iterable: ExprType, body: StatementType, span: Span) -> Self {
+        Self { variable, type_spec, iterable, body, id: gen_new_id(), span }
+    }
+    pub fn variable(&self) -> Token {
+        self.variable.clone()
+    }
+    pub fn type_spec(&self) -> TypeSpec {
+        self.type_spec.clone()
+    }
+    pub fn iterable(&self) -> ExprType {
+        self.iterable.clone()
+    }
+    pub fn body(&self) -> StatementType {
+        self.body.clone()
+    }
+    pub fn get_id(&self) -> usize {
+        self.id
+    }
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
 impl BlockStatement {
-    pub fn new(body: Vec<StatementType>) -> Self {
-        Self { body, id: gen_new_id() }
+    pub fn new(body: Vec<StatementType>, span: Span) -> Self {
+        Self { body, id: gen_new_id(), span }
+    }
+    pub fn get_id(&self) -> usize {
+        self.id
+    }
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+impl MatchExpression {
+    pub fn new(scrutinee: ExprType, arms: Vec<MatchArm>, span: Span) -> Self {
+        Self { scrutinee, arms, id: gen_new_id(), span }
+    }
+    pub fn scrutinee(&self) -> ExprType {
+        self.scrutinee.clone()
+    }
+    pub fn arms(&self) -> Vec<MatchArm> {
+        self.arms.clone()
+    }
+    pub fn get_id(&self) -> usize {
+        self.id
+    }
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl MatchArm {
+    pub fn new(pattern: Pattern, body: StatementType, span: Span) -> Self {
+        Self { pattern, body, id: gen_new_id(), span }
+    }
+    pub fn pattern(&self) -> Pattern {
+        self.pattern.clone()
+    }
+    pub fn body(&self) -> StatementType {
+        self.body.clone()
     }
     pub fn get_id(&self) -> usize {
         self.id
     }
-}
\ No newline at end of file
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Pattern {
+    pub fn span(&self) -> Span {
+        match self {
+            Pattern::Wildcard(span) |
+            Pattern::Binding(_, span) |
+            Pattern::Literal(_, span) |
+            Pattern::Variant(_, _, span) => *span,
+        }
+    }
+}
@@ -1,26 +1,226 @@
-use crate::driver::diag::DiagType;
-use super::{ast::{DeclerationType, FunctionDeclerationStatement, StatementType, TypeSpec}, parser::Parser, token::{Token, TokenType}};
-use std::process::exit;
+use super::{ast::{DeclerationType, ExprType, Field, FunctionDeclerationStatement, NamespaceDeclerationStatement, Parameter, EnumDeclerationStatement, StatementType, StructDeclerationStatement, TypeAliasDeclerationStatement, TypeSpec, Variant, VariableDeclerationStatement}, parser::Parser, span::Span, token::{Token, TokenType}};
 
 impl Parser {
-    pub fn parse_func_decleration(&mut self) -> FunctionDeclerationStatement  {
-        let name: Token = self.expect(true, TokenType::Identifier).unwrap();
+    pub fn parse_func_decleration(&mut self, start: Span) -> Option<FunctionDeclerationStatement> {
+        let external: bool = self.current_token.get_type() == TokenType::External;
+        if external {
+            self.consume();
+        }
+        let name: Token = self.expect(true, TokenType::Identifier)?;
         self.expect(true, TokenType::OpenParen);
+        let params: Vec<Parameter> = self.parse_parameters();
         self.expect(true, TokenType::CloseParen);
         self.expect(true, TokenType::Colon);
         let return_type: TypeSpec = self.parse_type_annotation();
-        let body: StatementType = self.parse_stmt();
-        FunctionDeclerationStatement::new(name, return_type, body)
+        if self.is_panicking() {
+            // The signature is broken, skip the body instead of parsing it out of context
+            return None;
+        }
+        // A `;` instead of a body declares a function that is defined elsewhere
+        let mut body: Option<StatementType> = None;
+        if self.current_token.get_type() == TokenType::Semicolon {
+            self.consume();
+        } else {
+            body = Some(self.parse_stmt());
+        }
+        Some(FunctionDeclerationStatement::new(name, params, return_type, body, external, self.span_from(start)))
+    }
+    pub fn parse_namespace_decleration(&mut self, start: Span) -> Option<NamespaceDeclerationStatement> {
+        let name: Token = self.expect(true, TokenType::Identifier)?;
+        self.expect(true, TokenType::OpenCurly)?;
+        let mut body: Vec<StatementType> = vec![];
+        self.namespace_depth += 1;
+        while !matches!(self.current_token.get_type(), TokenType::CloseCurly | TokenType::Eof) {
+            body.push(self.parse_top_stmt());
+        }
+        self.namespace_depth -= 1;
+        self.expect(true, TokenType::CloseCurly);
+        Some(NamespaceDeclerationStatement::new(name, body, self.span_from(start)))
+    }
+    // `struct Name { field: type, ... }`, a trailing comma after the last field is allowed
+    pub fn parse_struct_decleration(&mut self, start: Span) -> Option<StructDeclerationStatement> {
+        let name: Token = self.expect(true, TokenType::Identifier)?;
+        let type_params: Vec<Token> = if self.current_token.get_type() == TokenType::Less { self.parse_type_parameters()? } else { vec![] };
+        self.expect(true, TokenType::OpenCurly)?;
+        let mut fields: Vec<Field> = vec![];
+        while self.current_token.get_type() != TokenType::CloseCurly {
+            let Some(field) = self.parse_field() else {
+                break;
+            };
+            fields.push(field);
+            if self.current_token.get_type() != TokenType::Comma {
+                break;
+            }
+            self.consume();
+        }
+        if self.expect(true, TokenType::CloseCurly).is_none() {
+            // Skip the rest of the body so its `}` isn't taken for the end of a namespace
+            while !matches!(self.current_token.get_type(), TokenType::CloseCurly | TokenType::Eof) {
+                self.consume();
+            }
+            self.consume();
+            return None;
+        }
+        Some(StructDeclerationStatement::new(name, type_params, fields, self.span_from(start)))
+    }
+    // `enum Name { Variant, Variant(type, ...), ... }`, a trailing comma after the last variant is allowed
+    pub fn parse_enum_decleration(&mut self, start: Span) -> Option<EnumDeclerationStatement> {
+        let name: Token = self.expect(true, TokenType::Identifier)?;
+        self.expect(true, TokenType::OpenCurly)?;
+        let mut variants: Vec<Variant> = vec![];
+        while self.current_token.get_type() != TokenType::CloseCurly {
+            let Some(variant) = self.parse_variant() else {
+                break;
+            };
+            variants.push(variant);
+            if self.current_token.get_type() != TokenType::Comma {
+                break;
+            }
+            self.consume();
+        }
+        if self.expect(true, TokenType::CloseCurly).is_none() {
+            // Like a struct, skip the rest of the body so its `}` isn't taken for the end of a namespace
+            while !matches!(self.current_token.get_type(), TokenType::CloseCurly | TokenType::Eof) {
+                self.consume();
+            }
+            self.consume();
+            return None;
+        }
+        Some(EnumDeclerationStatement::new(name, variants, self.span_from(start)))
+    }
+    fn parse_variant(&mut self) -> Option<Variant> {
+        let start: Span = self.current_token.get_span();
+        let name: Token = self.expect(true, TokenType::Identifier)?;
+        let mut fields: Vec<TypeSpec> = vec![];
+        if self.current_token.get_type() == TokenType::OpenParen {
+            self.consume();
+            loop {
+                fields.push(self.parse_type_annotation());
+                if self.current_token.get_type() != TokenType::Comma {
+                    break;
+                }
+                self.consume();
+            }
+            self.expect(true, TokenType::CloseParen)?;
+        }
+        Some(Variant { name, fields, span: self.span_from(start) })
+    }
+    // `<T, U>` after the name of a generic struct
+    fn parse_type_parameters(&mut self) -> Option<Vec<Token>> {
+        self.consume();
+        let mut type_params: Vec<Token> = vec![];
+        loop {
+            type_params.push(self.expect(true, TokenType::Identifier)?);
+            if self.current_token.get_type() != TokenType::Comma {
+                break;
+            }
+            self.consume();
+        }
+        self.expect(true, TokenType::Greater)?;
+        Some(type_params)
+    }
+    fn parse_field(&mut self) -> Option<Field> {
+        let start: Span = self.current_token.get_span();
+        let name: Token = self.expect(true, TokenType::Identifier)?;
+        self.expect(true, TokenType::Colon)?;
+        let type_spec: TypeSpec = self.parse_type_annotation();
+        Some(Field { name, type_spec, span: self.span_from(start) })
+    }
+    // `name: type` pairs separated by commas, the parentheses are handled by the caller
+    fn parse_parameters(&mut self) -> Vec<Parameter> {
+        let mut params: Vec<Parameter> = vec![];
+        if self.current_token.get_type() == TokenType::CloseParen {
+            return params;
+        }
+        loop {
+            let start: Span = self.current_token.get_span();
+            // `var` is allowed but doesn't change anything, parameters are always variables
+            if self.current_token.get_type() == TokenType::Var {
+                self.consume();
+            }
+            let Some(name) = self.expect(true, TokenType::Identifier) else {
+                break;
+            };
+            self.expect(true, TokenType::Colon);
+            let type_spec: TypeSpec = self.parse_type_annotation();
+            params.push(Parameter { name, type_spec, span: self.span_from(start) });
+            if self.current_token.get_type() != TokenType::Comma {
+                break;
+            }
+            self.consume();
+        }
+        params
+    }
+    // `type Name = type;`
+    pub fn parse_type_alias_decleration(&mut self, start: Span) -> Option<TypeAliasDeclerationStatement> {
+        let name: Token = self.expect(true, TokenType::Identifier)?;
+        self.expect(true, TokenType::Equal)?;
+        let type_spec: TypeSpec = self.parse_type_annotation();
+        self.expect(true, TokenType::Semicolon)?;
+        Some(TypeAliasDeclerationStatement::new(name, type_spec, self.span_from(start)))
+    }
+    // `var name: type` with an optional `= initializer`, the `;` is handled by the statement
+    pub fn parse_var_decleration(&mut self, start: Span) -> Option<VariableDeclerationStatement> {
+        let name: Token = self.expect(true, TokenType::Identifier)?;
+        self.expect(true, TokenType::Colon);
+        let type_spec: TypeSpec = self.parse_type_annotation();
+        let mut initializer: Option<ExprType> = None;
+        if self.current_token.get_type() == TokenType::Equal {
+            self.consume();
+            initializer = Some(self.parse_expr(0));
+        }
+        Some(VariableDeclerationStatement::new(name, type_spec, initializer, self.span_from(start)))
     }
     pub fn parse_decleration(&mut self) -> DeclerationType {
+        let start: Span = self.current_token.get_span();
         match self.current_token.get_type() {
             TokenType::Func => {
                 self.consume();
-                DeclerationType::Function(Box::new(self.parse_func_decleration()))
+                match self.parse_func_decleration(start) {
+                    Some(func) => DeclerationType::Function(Box::new(func)),
+                    None => DeclerationType::Invalid(self.span_from(start)),
+                }
+            }
+            TokenType::Namespace => {
+                self.consume();
+                match self.parse_namespace_decleration(start) {
+                    Some(namespace) => DeclerationType::Namespace(Box::new(namespace)),
+                    None => DeclerationType::Invalid(self.span_from(start)),
+                }
+            }
+            TokenType::Struct => {
+                self.consume();
+                match self.parse_struct_decleration(start) {
+                    Some(struct_decl) => DeclerationType::Struct(Box::new(struct_decl)),
+                    None => DeclerationType::Invalid(self.span_from(start)),
+                }
+            }
+            TokenType::Enum => {
+                self.consume();
+                match self.parse_enum_decleration(start) {
+                    Some(enum_decl) => DeclerationType::Enum(Box::new(enum_decl)),
+                    None => DeclerationType::Invalid(self.span_from(start)),
+                }
+            }
+            TokenType::Type => {
+                self.consume();
+                match self.parse_type_alias_decleration(start) {
+                    Some(alias) => DeclerationType::TypeAlias(Box::new(alias)),
+                    None => DeclerationType::Invalid(self.span_from(start)),
+                }
+            }
+            TokenType::Var => {
+                self.consume();
+                match self.parse_var_decleration(start) {
+                    Some(var) => DeclerationType::Variable(Box::new(var)),
+                    None => DeclerationType::Invalid(self.span_from(start)),
+                }
             }
             _ => {
-                self.diag.print_formatted(DiagType::Ice, format!("Unhandeld decleration token: `{}`", self.current_token.get_data()));
-                exit(1);
+                self.error(start, format!("Unexpected token: `{}`", self.current_token.get_data()));
+                self.consume();
+                self.synchronize_top();
+                DeclerationType::Invalid(self.span_from(start))
             }
         }
     }
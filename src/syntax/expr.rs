@@ -1,55 +1,152 @@
-use crate::driver::diag::DiagType;
-use super::{ast::{ExprType, TypeSpec}, parser::Parser, token::{Token, TokenType}};
-use std::process::exit;
+use super::{ast::{ExprType, FieldInitializer, MatchArm, MatchExpression, Pattern, StatementType, TypeSpec}, parser::Parser, span::Span, token::{Token, TokenType}};
 
-fn get_precedency(token: TokenType) -> u8 {
-    match token {
-        TokenType::Plus => 1,
-        _ => 0,
-    }
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Associativity {
+    Left,
+    Right,
+}
+
+// Binary operators from loosest to tightest binding
+const BINARY_OPERATORS: &[(TokenType, u8, Associativity)] = &[
+    (TokenType::Equal, ASSIGNMENT_PRECEDENCE, Associativity::Right),
+    (TokenType::PlusEqual, ASSIGNMENT_PRECEDENCE, Associativity::Right),
+    (TokenType::MinusEqual, ASSIGNMENT_PRECEDENCE, Associativity::Right),
+    (TokenType::StarEqual, ASSIGNMENT_PRECEDENCE, Associativity::Right),
+    (TokenType::SlashEqual, ASSIGNMENT_PRECEDENCE, Associativity::Right),
+    (TokenType::PercentEqual, ASSIGNMENT_PRECEDENCE, Associativity::Right),
+    (TokenType::AmpersandEqual, ASSIGNMENT_PRECEDENCE, Associativity::Right),
+    (TokenType::PipeEqual, ASSIGNMENT_PRECEDENCE, Associativity::Right),
+    (TokenType::CaretEqual, ASSIGNMENT_PRECEDENCE, Associativity::Right),
+    (TokenType::LessLessEqual, ASSIGNMENT_PRECEDENCE, Associativity::Right),
+    (TokenType::GreaterGreaterEqual, ASSIGNMENT_PRECEDENCE, Associativity::Right),
+    (TokenType::PipePipe, 2, Associativity::Left),
+    (TokenType::AmpersandAmpersand, 3, Associativity::Left),
+    (TokenType::Pipe, 4, Associativity::Left),
+    (TokenType::Caret, 5, Associativity::Left),
+    (TokenType::Ampersand, 6, Associativity::Left),
+    (TokenType::EqualEqual, 7, Associativity::Left),
+    (TokenType::BangEqual, 7, Associativity::Left),
+    (TokenType::Less, 8, Associativity::Left),
+    (TokenType::LessEqual, 8, Associativity::Left),
+    (TokenType::Greater, 8, Associativity::Left),
+    (TokenType::GreaterEqual, 8, Associativity::Left),
+    (TokenType::LessLess, 9, Associativity::Left),
+    (TokenType::GreaterGreater, 9, Associativity::Left),
+    (TokenType::Plus, 10, Associativity::Left),
+    (TokenType::Minus, 10, Associativity::Left),
+    (TokenType::Star, 11, Associativity::Left),
+    (TokenType::Slash, 11, Associativity::Left),
+    (TokenType::Percent, 11, Associativity::Left),
+];
+
+const ASSIGNMENT_PRECEDENCE: u8 = 1;
+
+// `as` binds tighter than every binary operator but looser than prefix operators,
+// so `-x as int * 2` is `((-x) as int) * 2`
+const CAST_PRECEDENCE: u8 = 12;
+
+const PREFIX_OPERATORS: &[TokenType] = &[
+    TokenType::Minus,
+    TokenType::Bang,
+    TokenType::Tilde,
+    TokenType::Star,
+    TokenType::Ampersand,
+];
+
+// Whether an operand can begin with `token`
+pub fn can_start_expr(token: TokenType) -> bool {
+    matches!(token, TokenType::Identifier | TokenType::NumericLiteral | TokenType::FloatLiteral | TokenType::StringLiteral | TokenType::CharLiteral | TokenType::True | TokenType::False | TokenType::Null | TokenType::Match | TokenType::OpenParen) || PREFIX_OPERATORS.contains(&token)
+}
+
+fn get_precedency(token: TokenType) -> Option<(u8, Associativity)> {
+    BINARY_OPERATORS.iter().find(|(token_type, _, _)| *token_type == token).map(|(_, precedency, associativity)| (*precedency, *associativity))
 }
 
 impl Parser {
     pub fn parse_primary_expr(&mut self) -> ExprType {
         match self.current_token.get_type() {
             TokenType::Identifier => {
-                let ret: ExprType = ExprType::Identifier(self.current_token.clone());
+                let ret: ExprType = ExprType::identifier(self.current_token.clone(), self.current_token.get_span());
                 self.consume();
                 ret
             }
             TokenType::StringLiteral => {
-                let ret: ExprType = ExprType::StringLiteral(self.current_token.clone());
+                let ret: ExprType = ExprType::StringLiteral(self.current_token.clone(), self.current_token.get_span());
                 self.consume();
                 ret
             }
             TokenType::NumericLiteral => {
-                let ret: ExprType = ExprType::NumericLiteral(self.current_token.clone());
+                let ret: ExprType = ExprType::NumericLiteral(self.current_token.clone(), self.current_token.get_span());
+                self.consume();
+                ret
+            }
+            TokenType::FloatLiteral => {
+                let ret: ExprType = ExprType::FloatLiteral(self.current_token.clone(), self.current_token.get_span());
+                self.consume();
+                ret
+            }
+            TokenType::CharLiteral => {
+                let ret: ExprType = ExprType::CharLiteral(self.current_token.clone(), self.current_token.get_span());
+                self.consume();
+                ret
+            }
+            TokenType::True | TokenType::False => {
+                let ret: ExprType = ExprType::BoolLiteral(self.current_token.clone(), self.current_token.get_span());
+                self.consume();
+                ret
+            }
+            TokenType::Null => {
+                let ret: ExprType = ExprType::NullLiteral(self.current_token.clone(), self.current_token.get_span());
                 self.consume();
                 ret
             }
             TokenType::OpenParen => {
                 self.consume();
+                // Parentheses end a `match` scrutinee's restriction on struct literals
+                let no_struct_literal: bool = std::mem::replace(&mut self.no_struct_literal, false);
                 let expr: ExprType = self.parse_expr(0);
+                self.no_struct_literal = no_struct_literal;
                 self.expect(true, TokenType::CloseParen);
                 expr
             }
+            TokenType::Match => {
+                let start: Span = self.current_token.get_span();
+                self.parse_match(start)
+            }
             _ => {
-                self.diag.print_formatted(DiagType::Error, format!("Unexpected token: `{}`", self.current_token.get_data()));
-                exit(1);
+                let span: Span = self.current_token.get_span();
+                self.error(span, format!("Unexpected token: `{}`", self.current_token.get_data()));
+                ExprType::Invalid(span)
             }
         }
     }
     pub fn parse_postfix_expr(&mut self) -> ExprType {
+        let start: Span = self.current_token.get_span();
         let mut member: ExprType = self.parse_primary_expr();
         loop {
             match self.current_token.get_type() {
                 TokenType::ColonColon => {
                     self.consume();
                     let property = self.parse_primary_expr();
-                    member = ExprType::MemberAccess(Box::new(member), Box::new(property));
+                    member = ExprType::MemberAccess(Box::new(member), Box::new(property), self.span_from(start));
+                }
+                TokenType::Dot => {
+                    self.consume();
+                    let Some(field) = self.expect(true, TokenType::Identifier) else {
+                        return ExprType::Invalid(self.span_from(start));
+                    };
+                    member = ExprType::FieldAccess(Box::new(member), field, self.span_from(start));
+                }
+                // Only a name can start a struct literal, so `if (x) {` is never one
+                TokenType::OpenCurly if !self.no_struct_literal && matches!(member, ExprType::Identifier(..) | ExprType::MemberAccess(..)) => {
+                    self.consume();
+                    let fields: Vec<FieldInitializer> = self.parse_field_initializers();
+                    self.expect(true, TokenType::CloseCurly);
+                    member = ExprType::StructLiteral(Box::new(member), fields, self.span_from(start));
                 }
                 TokenType::OpenParen => {
                     self.consume();
+                    let no_struct_literal: bool = std::mem::replace(&mut self.no_struct_literal, false);
                     let mut args: Vec<ExprType> = vec![];
                     if self.current_token.get_type() != TokenType::CloseParen {
                         loop {
@@ -60,11 +157,9 @@ impl Parser {
                             self.consume();
                         }
                     }
-                    if self.consume().get_type() != TokenType::CloseParen {
-                        self.diag.print_formatted(DiagType::Error, format!("Expected `)` but got `{}` instead", self.current_token.get_data()));
-                        exit(1);
-                    }
-                    member = ExprType::Call(Box::new(member), args);
+                    self.no_struct_literal = no_struct_literal;
+                    self.expect(true, TokenType::CloseParen);
+                    member = ExprType::Call(Box::new(member), args, self.span_from(start));
                 }
                 _ => {
                     break;
@@ -73,28 +168,153 @@ impl Parser {
         }
         member
     }
-    pub fn parse_expr(&mut self, min_prec: u8) -> ExprType {
-        let mut lhs: ExprType = self.parse_postfix_expr();
-        while let TokenType::Plus | TokenType::Minus = self.current_token.get_type() {
-            match self.current_token.get_type() {
-                TokenType::Plus | TokenType::Minus => {
-                    let operation: Token = self.current_token.clone();
-                    let precedency: u8 = get_precedency(self.current_token.get_type());
-                    if precedency < min_prec {
-                        break;
-                    }
+    // `match scrutinee { pattern => body, ... }`, a body is an expression or a block,
+    // the `,` after a block is optional
+    pub fn parse_match(&mut self, start: Span) -> ExprType {
+        self.expect(true, TokenType::Match);
+        let no_struct_literal: bool = std::mem::replace(&mut self.no_struct_literal, true);
+        let scrutinee: ExprType = self.parse_expr(0);
+        self.no_struct_literal = no_struct_literal;
+        if self.expect(true, TokenType::OpenCurly).is_none() {
+            return ExprType::Invalid(self.span_from(start));
+        }
+        let mut arms: Vec<MatchArm> = vec![];
+        while !matches!(self.current_token.get_type(), TokenType::CloseCurly | TokenType::Eof) && !self.is_panicking() {
+            let arm_start: Span = self.current_token.get_span();
+            let pattern: Pattern = self.parse_pattern();
+            self.expect(true, TokenType::FatArrow);
+            let body_start: Span = self.current_token.get_span();
+            let body: StatementType = if self.current_token.get_type() == TokenType::OpenCurly {
+                self.consume();
+                StatementType::Block(self.parse_block_stmt(body_start))
+            } else {
+                let expr: ExprType = self.parse_expr(0);
+                StatementType::Expr(expr, self.span_from(body_start))
+            };
+            let block: bool = matches!(body, StatementType::Block(_));
+            arms.push(MatchArm::new(pattern, body, self.span_from(arm_start)));
+            if self.current_token.get_type() == TokenType::Comma {
+                self.consume();
+            } else if !block && self.current_token.get_type() != TokenType::CloseCurly {
+                self.expect(true, TokenType::Comma);
+            }
+        }
+        self.expect(true, TokenType::CloseCurly);
+        ExprType::Match(Box::new(MatchExpression::new(scrutinee, arms, self.span_from(start))))
+    }
+    // `_`, a name to bind, a literal, or an `Enum::Variant` path with patterns for its fields
+    fn parse_pattern(&mut self) -> Pattern {
+        let start: Span = self.current_token.get_span();
+        match self.current_token.get_type() {
+            TokenType::Identifier if self.current_token.get_data() == "_" => {
+                self.consume();
+                Pattern::Wildcard(start)
+            }
+            TokenType::Identifier if !matches!(self.peek(), TokenType::ColonColon | TokenType::OpenParen) => {
+                let name: Token = self.consume();
+                Pattern::Binding(name, start)
+            }
+            TokenType::Identifier => {
+                let mut path: ExprType = ExprType::identifier(self.current_token.clone(), start);
+                self.consume();
+                while self.current_token.get_type() == TokenType::ColonColon {
+                    self.consume();
+                    let Some(segment) = self.expect(true, TokenType::Identifier) else {
+                        return Pattern::Wildcard(self.span_from(start));
+                    };
+                    let segment_span: Span = segment.get_span();
+                    path = ExprType::MemberAccess(Box::new(path), Box::new(ExprType::identifier(segment, segment_span)), self.span_from(start));
+                }
+                let mut fields: Vec<Pattern> = vec![];
+                if self.current_token.get_type() == TokenType::OpenParen {
                     self.consume();
-                    let rhs: ExprType = self.parse_expr(precedency + 1);
-                    lhs = ExprType::Binary(Box::new(lhs), operation, Box::new(rhs));
+                    while self.current_token.get_type() != TokenType::CloseParen {
+                        fields.push(self.parse_pattern());
+                        if self.current_token.get_type() != TokenType::Comma || self.is_panicking() {
+                            break;
+                        }
+                        self.consume();
+                    }
+                    self.expect(true, TokenType::CloseParen);
                 }
-                _ => { break }
+                Pattern::Variant(path, fields, self.span_from(start))
+            }
+            TokenType::NumericLiteral | TokenType::FloatLiteral | TokenType::StringLiteral | TokenType::CharLiteral | TokenType::True | TokenType::False => {
+                let literal: ExprType = self.parse_primary_expr();
+                Pattern::Literal(literal, start)
+            }
+            TokenType::Minus if matches!(self.peek(), TokenType::NumericLiteral | TokenType::FloatLiteral) => {
+                let op: Token = self.consume();
+                let literal: ExprType = self.parse_primary_expr();
+                let span: Span = self.span_from(start);
+                Pattern::Literal(ExprType::Unary(op, Box::new(literal), span), span)
+            }
+            _ => {
+                self.error(start, format!("Expected a pattern but got `{}` instead", self.current_token.get_data()));
+                Pattern::Wildcard(start)
             }
         }
-        if self.current_token.get_type() == TokenType::As {
+    }
+    // `field: value` pairs of a struct literal, a trailing comma is allowed
+    fn parse_field_initializers(&mut self) -> Vec<FieldInitializer> {
+        let mut fields: Vec<FieldInitializer> = vec![];
+        while self.current_token.get_type() != TokenType::CloseCurly {
+            let start: Span = self.current_token.get_span();
+            let Some(name) = self.expect(true, TokenType::Identifier) else {
+                break;
+            };
+            self.expect(true, TokenType::Colon);
+            let value: ExprType = self.parse_expr(0);
+            fields.push(FieldInitializer { name, value, span: self.span_from(start) });
+            if self.current_token.get_type() != TokenType::Comma {
+                break;
+            }
             self.consume();
-            let type_spec: TypeSpec = self.parse_type_annotation();
-            lhs = ExprType::Cast(Box::new(lhs), type_spec);
+        }
+        fields
+    }
+    pub fn parse_unary_expr(&mut self) -> ExprType {
+        if PREFIX_OPERATORS.contains(&self.current_token.get_type()) {
+            let operation: Token = self.consume();
+            let operand: ExprType = self.parse_unary_expr();
+            let span: Span = self.span_from(operation.get_span());
+            return ExprType::Unary(operation, Box::new(operand), span);
+        }
+        self.parse_postfix_expr()
+    }
+    // Precedence climbing, `min_prec` is the loosest operator this call may consume
+    pub fn parse_expr(&mut self, min_prec: u8) -> ExprType {
+        let mut lhs: ExprType = self.parse_unary_expr();
+        loop {
+            if self.current_token.get_type() == TokenType::As {
+                if CAST_PRECEDENCE < min_prec {
+                    break;
+                }
+                self.consume();
+                let type_spec: TypeSpec = self.parse_type_annotation();
+                let span: Span = self.span_from(lhs.span());
+                lhs = ExprType::Cast(Box::new(lhs), type_spec, span);
+                continue;
+            }
+            let Some((precedency, associativity)) = get_precedency(self.current_token.get_type()) else {
+                break;
+            };
+            if precedency < min_prec {
+                break;
+            }
+            let operation: Token = self.consume();
+            let next_min_prec: u8 = match associativity {
+                Associativity::Left => precedency + 1,
+                Associativity::Right => precedency,
+            };
+            let rhs: ExprType = self.parse_expr(next_min_prec);
+            let span: Span = lhs.span().merge(rhs.span());
+            if precedency == ASSIGNMENT_PRECEDENCE {
+                lhs = ExprType::Assign(Box::new(lhs), operation, Box::new(rhs), span);
+            } else {
+                lhs = ExprType::Binary(Box::new(lhs), operation, Box::new(rhs), span);
+            }
         }
         lhs
     }
-}
\ No newline at end of file
+}
@@ -1,20 +1,24 @@
 use crate::driver::diag::{DiagType, DiagPrinter};
 use crate::syntax::token::Token;
+use crate::syntax::span::Span;
 use std::string::String;
-use std::process::exit;
 
-use super::token::TokenType;
+use super::token::{TokenType, KEYWORDS, PUNCTUATORS};
 
+// Walks the input once, `current_index` is the byte offset of `current_char` so every
+// step is constant time. `current_char` is `\0` once the end of the input is reached
 pub struct Lexer {
     file_data: String,
     pub current_index: usize,
     pub current_char: char,
-    first_run: bool,
+    line: usize,
+    column: usize,
+    steps: usize, // Times the cursor moved, every character is stepped over exactly once
     diag: DiagPrinter,
 }
 
 fn is_whitespace(c: char) -> bool {
-    if c == ' ' || c == '\n' {
+    if c == ' ' || c == '\n' || c == '\t' || c == '\r' {
         return true;
     }
     false
@@ -27,142 +31,371 @@ fn is_identifier(c: char) -> bool {
     false
 }
 
-const KEYWRODS: &[(&str, TokenType)] = &[
-    ("func", TokenType::Func),
-    ("int", TokenType::Int),
-    ("string", TokenType::String),
-    ("return", TokenType::Return),
-    ("as", TokenType::As),
-];
+fn get_keyword(value: &str, span: Span) -> Token {
+    for (lexme, token_type) in KEYWORDS {
+        if *lexme == value {
+            return Token::new(*token_type, value.to_string(), span);
+        }
+    }
+    Token::new(TokenType::Identifier, value.to_string(), span)
+}
+
+fn radix_name(radix: u32) -> &'static str {
+    match radix {
+        2 => "binary",
+        8 => "octal",
+        16 => "hexadecimal",
+        _ => "decimal",
+    }
+}
+
+// Value and suffix of an integer literal as lexed by `Lexer::parse_numeric`,
+// the value is `None` if it doesn't even fit in a `u128`
+pub fn integer_literal_value(literal: &str) -> (Option<u128>, &str) {
+    let (radix, digits): (u32, &str) = match literal.get(..2) {
+        Some("0x") => (16, &literal[2..]),
+        Some("0b") => (2, &literal[2..]),
+        Some("0o") => (8, &literal[2..]),
+        _ => (10, literal),
+    };
+    let suffix_start: usize = digits.find(|c: char| c != '_' && !c.is_digit(radix)).unwrap_or(digits.len());
+    let mut value: Option<u128> = Some(0);
+    for digit in digits[..suffix_start].chars().filter_map(|c| c.to_digit(radix)) {
+        value = value.and_then(|value| value.checked_mul(radix as u128)).and_then(|value| value.checked_add(digit as u128));
+    }
+    (value, &digits[suffix_start..])
+}
+
+// End of the run of `radix` digits and `_` separators starting at `from`
+fn digits_end(text: &str, from: usize, radix: u32) -> usize {
+    from + text[from..].find(|c: char| c != '_' && !c.is_digit(radix)).unwrap_or(text.len() - from)
+}
 
-fn get_keyword(value: String) -> Token {
-    for (lexme, token_type) in KEYWRODS {
-        if *lexme.to_string() == value {
-            return Token::new(*token_type, value);
+// Value and suffix of a float literal as lexed by `Lexer::parse_numeric`. Hexadecimal
+// floats are a mantissa scaled by a power of two, `0x1.8p1` is 3. The value is infinite
+// if it's too large for an `f64` and `None` if the exponent doesn't even fit in an `i32`
+pub fn float_literal_value(literal: &str) -> (Option<f64>, &str) {
+    let (radix, exponent_chars, digits): (u32, [char; 2], &str) = match literal.strip_prefix("0x") {
+        Some(digits) => (16, ['p', 'P'], digits),
+        None => (10, ['e', 'E'], literal),
+    };
+    let mut end: usize = digits_end(digits, 0, radix);
+    if digits[end..].starts_with('.') {
+        end = digits_end(digits, end + 1, radix);
+    }
+    let mantissa: &str = &digits[..end];
+    let mut exponent: Option<i32> = Some(0);
+    if digits[end..].starts_with(exponent_chars) {
+        let sign: usize = if digits[end + 1..].starts_with(['+', '-']) { 1 } else { 0 };
+        if digits[end + 1 + sign..].starts_with(|c: char| c.is_ascii_digit()) {
+            let exponent_end: usize = digits_end(digits, end + 1 + sign, 10);
+            exponent = digits[end + 1..exponent_end].replace('_', "").parse().ok();
+            end = exponent_end;
         }
     }
-    Token::new(TokenType::Identifier, value)
+    let suffix: &str = &digits[end..];
+    if radix == 10 {
+        let text: String = format!("{}e{}", mantissa.replace('_', ""), exponent.unwrap_or(i32::MAX));
+        return (exponent.and(text.parse().ok()), suffix);
+    }
+    let (whole, fraction): (&str, &str) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let mut value: f64 = 0.0;
+    for digit in whole.chars().chain(fraction.chars()).filter_map(|c| c.to_digit(16)) {
+        value = value * 16.0 + digit as f64;
+    }
+    let fraction_digits: i32 = fraction.chars().filter(|c| c.is_ascii_hexdigit()).count() as i32;
+    let value: Option<f64> = exponent.map(|exponent| value * 2f64.powi(exponent.saturating_sub(fraction_digits * 4)));
+    (value, suffix)
 }
 
 impl Lexer {
     pub fn new(file_data: String, diag_printer: DiagPrinter) -> Self {
-        Self { file_data, current_index: 0, current_char: '\0', first_run: true, diag: diag_printer }
+        let current_char: char = file_data.chars().next().unwrap_or('\0');
+        Self { file_data, current_index: 0, current_char, line: 1, column: 1, steps: 0, diag: diag_printer }
+    }
+    fn at_end(&self) -> bool {
+        self.current_index >= self.file_data.len()
     }
     fn next_char(&mut self){
-        self.current_char = self.file_data.chars().nth(self.current_index).unwrap_or('\0');
-        self.current_index += 1;
+        if self.at_end() {
+            return;
+        }
+        self.current_index += self.current_char.len_utf8();
+        self.steps += 1;
+        if self.current_char == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        self.current_char = self.file_data[self.current_index..].chars().next().unwrap_or('\0');
     }
-    fn skip_whitespace(&mut self) {
-        while is_whitespace(self.current_char) {
-            self.next_char();
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+    fn peek_char(&self) -> char {
+        if self.at_end() {
+            return '\0';
+        }
+        self.file_data[self.current_index + self.current_char.len_utf8()..].chars().next().unwrap_or('\0')
+    }
+    // Span of the character under the cursor, used as the start of a token
+    fn location(&self) -> Span {
+        Span::new(self.current_index, self.current_index, self.line, self.column)
+    }
+    fn span_from(&self, start: Span) -> Span {
+        Span { end: self.current_index, ..start }
+    }
+    fn text_from(&self, start: Span) -> &str {
+        &self.file_data[start.start..self.current_index]
+    }
+    fn skip_whitespace_and_comments(&mut self) {
+        loop {
+            if is_whitespace(self.current_char) {
+                self.next_char();
+            } else if self.current_char == '/' && self.peek_char() == '/' {
+                while self.current_char != '\n' && !self.at_end() {
+                    self.next_char();
+                }
+            } else {
+                break;
+            }
         }
     }
     fn parse_keywordidentifier(&mut self) -> Token {
-        let mut value: String = String::default();
+        let start: Span = self.location();
         while is_identifier(self.current_char) {
-            value.push(self.current_char);
             self.next_char();
         }
-        get_keyword(value)
+        get_keyword(self.text_from(start), self.span_from(start))
     }
+    // Decimal, `0x`, `0b` or `0o` digits with optional `_` separators, then an optional
+    // type suffix like `255u8`. A fraction or exponent makes it a float: `1.5`, `2e10`, `0x1p3`,
+    // as does a float suffix like `1f32`. Values are computed by `integer_literal_value` and
+    // `float_literal_value` when needed
     fn parse_numeric(&mut self) -> Token {
-        let mut value: String = String::default();
-        while self.current_char.is_ascii_digit() {
-            value.push(self.current_char);
+        let start: Span = self.location();
+        let radix: u32 = match (self.current_char, self.peek_char()) {
+            ('0', 'x') => 16,
+            ('0', 'b') => 2,
+            ('0', 'o') => 8,
+            _ => 10,
+        };
+        if radix != 10 {
             self.next_char();
-        }
-        Token::new(TokenType::NumericLiteral, value)
-    }
-    fn parse_string(&mut self) -> Token {
-        let mut value: String = String::default();
-        self.next_char();
-        while self.current_char != '"' && self.current_index < self.file_data.len() {
-            value.push(self.current_char);
             self.next_char();
         }
-        if self.current_index >= self.file_data.len() {
-            self.diag.print_formatted(DiagType::Error, "Unterminated string".to_string());
+        let digits: usize = self.parse_digits(radix);
+        if digits == 0 {
+            self.diag.print_formatted_at(DiagType::Error, self.span_from(start), format!("Missing digits in {} literal", radix_name(radix)));
         }
-        self.next_char();
-        Token::new(TokenType::StringLiteral, value)
-    }
-    fn parse_singletoken(&mut self) -> Token {
-        let token_type: TokenType;
-        let mut value: String = String::default();
-        match self.current_char {
-            '(' => {
-                value.push('(');
+        let mut is_float: bool = false;
+        // `1.5` and `0x1.8p1`, but not `1.field`
+        if matches!(radix, 10 | 16) && self.current_char == '.' && self.peek_char().is_digit(radix) {
+            self.next_char();
+            self.parse_digits(radix);
+            is_float = true;
+        }
+        let exponent_char: Option<char> = match radix {
+            10 => Some('e'),
+            16 => Some('p'),
+            _ => None,
+        };
+        let next: char = self.peek_char();
+        if exponent_char == Some(self.current_char.to_ascii_lowercase()) && (next.is_ascii_digit() || next == '+' || next == '-') {
+            self.next_char();
+            if matches!(self.current_char, '+' | '-') {
                 self.next_char();
-                token_type = TokenType::OpenParen;
             }
-            ')' => {
-                value.push(')');
-                self.next_char();
-                token_type = TokenType::CloseParen;
+            if self.parse_digits(10) == 0 {
+                self.error_at_current("Missing digits in exponent".to_string());
             }
-            '{' => {
-                value.push('{');
+            is_float = true;
+        } else if radix == 16 && is_float {
+            self.error_at_current("Hexadecimal float literal needs a `p` exponent".to_string());
+        }
+        let suffix_start: usize = self.current_index;
+        while is_identifier(self.current_char) {
+            self.next_char();
+        }
+        is_float |= radix == 10 && matches!(&self.file_data[suffix_start..self.current_index], "f32" | "f64");
+        let token_type: TokenType = if is_float { TokenType::FloatLiteral } else { TokenType::NumericLiteral };
+        Token::new(token_type, self.text_from(start).to_string(), self.span_from(start))
+    }
+    // Digits of `radix` with `_` separators, returns how many digits there were.
+    // Decimal digits in a smaller radix are reported but still consumed
+    fn parse_digits(&mut self, radix: u32) -> usize {
+        let mut digits: usize = 0;
+        loop {
+            if self.current_char == '_' {
                 self.next_char();
-                token_type = TokenType::OpenCurly;
+                continue;
             }
-            '}' => {
-                value.push('}');
-                self.next_char();
-                token_type = TokenType::CloseCurly;
+            let is_digit: bool = self.current_char.is_ascii_digit() || (radix == 16 && self.current_char.is_ascii_hexdigit());
+            if !is_digit {
+                break;
             }
-            ';' => {
-                value.push(';');
-                self.next_char();
-                token_type = TokenType::Semicolon;
+            if self.current_char.to_digit(radix).is_none() {
+                self.error_at_current(format!("Invalid digit `{}` in {} literal", self.current_char, radix_name(radix)));
             }
-            ':' => {
-                value.push(':');
-                self.next_char();
-                if self.current_char == ':' {
-                    value.push(':');
-                    self.next_char();
-                    token_type = TokenType::ColonColon;
-                } else {
-                    token_type = TokenType::Colon;
+            digits += 1;
+            self.next_char();
+        }
+        digits
+    }
+    fn parse_string(&mut self) -> Token {
+        let start: Span = self.location();
+        self.next_char();
+        let mut value: String = String::new();
+        while self.current_char != '"' && !self.at_end() {
+            if self.current_char == '\\' {
+                // Invalid escapes are reported and left out of the value
+                if let Some(decoded) = self.parse_escape() {
+                    value.push(decoded);
                 }
-            }
-            '+' => {
-                value.push('+');
+            } else {
+                value.push(self.current_char);
                 self.next_char();
-                if self.current_char == '=' {
-                    value.push('=');
-                    self.next_char();
-                    token_type = TokenType::PlusEqual;
-                } else if self.current_char == '+' {
-                    value.push('+');
-                    self.next_char();
-                    token_type = TokenType::PlusPlus;
-                } else {
-                    token_type = TokenType::Plus;
-                }
             }
+        }
+        if self.at_end() {
+            self.diag.print_formatted_at(DiagType::Error, self.span_from(start), "Unterminated string".to_string());
+        }
+        self.next_char();
+        Token::with_raw(TokenType::StringLiteral, value, self.text_from(start).to_string(), self.span_from(start))
+    }
+    // Reports `message` at the character under the cursor
+    fn error_at_current(&self, message: String) {
+        let start: Span = self.location();
+        let span: Span = Span { end: start.start + if self.at_end() { 0 } else { self.current_char.len_utf8() }, ..start };
+        self.diag.print_formatted_at(DiagType::Error, span, message);
+    }
+    // The character after a `\`, the cursor is on the `\` and is left after the sequence.
+    // Characters that can't be part of the sequence are reported but not consumed
+    fn parse_escape(&mut self) -> Option<char> {
+        self.next_char();
+        if self.at_end() {
+            return None;
+        }
+        let start: Span = self.location();
+        let name: char = self.current_char;
+        if name == '\n' {
+            self.error_at_current("Expected an escape sequence after `\\`".to_string());
+            return None;
+        }
+        self.next_char();
+        match name {
+            'n' => Some('\n'),
+            't' => Some('\t'),
+            'r' => Some('\r'),
+            '0' => Some('\0'),
+            '\\' => Some('\\'),
+            '\'' => Some('\''),
+            '"' => Some('"'),
+            'x' => self.parse_hex_escape(),
+            'u' => self.parse_unicode_escape(),
             _ => {
-                self.diag.print_formatted(DiagType::Ice, format!("Unhandled token `{}`", self.current_char));
-                exit(1);
+                self.diag.print_formatted_at(DiagType::Error, self.span_from(start), format!("Unknown escape sequence `\\{}`", name));
+                None
             }
         }
-        Token::new(token_type, value)
     }
-    pub fn next_token(&mut self) -> Token {
-        self.skip_whitespace();
-        if self.first_run {
+    // `\xNN`, exactly two hex digits for an ASCII character
+    fn parse_hex_escape(&mut self) -> Option<char> {
+        let start: Span = self.location();
+        let mut value: u32 = 0;
+        for _ in 0..2 {
+            let Some(digit) = self.current_char.to_digit(16) else {
+                self.error_at_current(format!("Invalid character `{}` in `\\x` escape, expected two hex digits", self.current_char.escape_debug()));
+                return None;
+            };
+            value = value * 16 + digit;
             self.next_char();
-            self.first_run = false;
         }
-        if self.current_char == '/' && self.file_data.chars().nth(self.current_index).unwrap_or('\0') == '/' {
-            while self.current_char != '\n' {
+        if value > 0x7F {
+            self.diag.print_formatted_at(DiagType::Error, self.span_from(start), format!("`\\x{:02X}` is out of range, `\\x` escapes only go up to `\\x7F`, use `\\u{{...}}` for other characters", value));
+            return None;
+        }
+        char::from_u32(value)
+    }
+    // `\u{...}`, one to six hex digits naming a unicode scalar value
+    fn parse_unicode_escape(&mut self) -> Option<char> {
+        if self.current_char != '{' {
+            self.error_at_current(format!("Expected `{{` after `\\u` but got `{}`", self.current_char.escape_debug()));
+            return None;
+        }
+        self.next_char();
+        let start: Span = self.location();
+        let mut value: u32 = 0;
+        let mut digits: usize = 0;
+        while self.current_char != '}' {
+            let Some(digit) = self.current_char.to_digit(16) else {
+                self.error_at_current(format!("Invalid character `{}` in `\\u{{...}}` escape, expected a hex digit or `}}`", self.current_char.escape_debug()));
+                return None;
+            };
+            if digits == 6 {
+                self.error_at_current("`\\u{...}` escape has more than 6 hex digits".to_string());
+                return None;
+            }
+            value = value * 16 + digit;
+            digits += 1;
+            self.next_char();
+        }
+        let span: Span = self.span_from(start);
+        self.next_char();
+        if digits == 0 {
+            self.diag.print_formatted_at(DiagType::Error, self.span_from(start), "Empty `\\u{}` escape".to_string());
+            return None;
+        }
+        let decoded: Option<char> = char::from_u32(value);
+        if decoded.is_none() {
+            self.diag.print_formatted_at(DiagType::Error, span, format!("`{:X}` is not a valid unicode character", value));
+        }
+        decoded
+    }
+    // `'x'` or an escape like `'\n'`, exactly one character between the quotes
+    fn parse_char(&mut self) -> Token {
+        let start: Span = self.location();
+        self.next_char();
+        let mut value: Option<char> = None;
+        let mut length: usize = 0;
+        while self.current_char != '\'' && self.current_char != '\n' && !self.at_end() {
+            let decoded: Option<char> = if self.current_char == '\\' {
+                self.parse_escape()
+            } else {
+                let current: char = self.current_char;
                 self.next_char();
+                Some(current)
+            };
+            value = value.or(decoded);
+            length += 1;
+        }
+        // Malformed literals are still char literals so the parser doesn't report them again
+        if self.current_char != '\'' {
+            self.diag.print_formatted_at(DiagType::Error, self.span_from(start), "Unterminated character literal".to_string());
+        } else {
+            self.next_char();
+            match length {
+                0 => self.diag.print_formatted_at(DiagType::Error, self.span_from(start), "Empty character literal".to_string()),
+                1 => {}
+                _ => self.diag.print_formatted_at(DiagType::Error, self.span_from(start), "Character literal may only contain one character".to_string()),
             }
+        }
+        Token::with_raw(TokenType::CharLiteral, value.unwrap_or('\0').to_string(), self.text_from(start).to_string(), self.span_from(start))
+    }
+    fn parse_punctuator(&mut self) -> Option<Token> {
+        let start: Span = self.location();
+        let rest: &str = &self.file_data[self.current_index..];
+        let (lexme, token_type) = PUNCTUATORS.iter().find(|(lexme, _)| rest.starts_with(lexme))?;
+        for _ in 0..lexme.len() {
             self.next_char();
-            self.skip_whitespace();
         }
-        if self.current_char == '\0' {
-            return Token::new(TokenType::Eof, "\0".to_string());
+        Some(Token::new(*token_type, lexme.to_string(), self.span_from(start)))
+    }
+    pub fn next_token(&mut self) -> Token {
+        self.skip_whitespace_and_comments();
+        if self.at_end() {
+            return Token::new(TokenType::Eof, "\0".to_string(), self.location());
         }
         match self.current_char {
             'a'..='z' | 'A'..='Z' | '_' => {
@@ -174,18 +407,18 @@ impl Lexer {
             '"' => {
                 self.parse_string()
             }
-            ':' |
-            ';' |
-            '+' |
-            '(' |
-            ')' |
-            '{' |
-            '}' => {
-                self.parse_singletoken()
+            '\'' => {
+                self.parse_char()
             }
             _ => {
-                self.diag.print_formatted(DiagType::Error, format!("Unknown token `{}`", self.current_char));
-                exit(1);
+                if let Some(token) = self.parse_punctuator() {
+                    return token;
+                }
+                let start: Span = self.location();
+                let value: String = self.current_char.to_string();
+                self.diag.print_formatted_at(DiagType::Error, start, format!("Unknown token `{}`", self.current_char));
+                self.next_char();
+                Token::new(TokenType::Error, value, self.span_from(start))
             }
         }
     }
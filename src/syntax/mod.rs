@@ -1,5 +1,6 @@
 pub mod lexer;
 pub mod token;
+pub mod span;
 pub mod parser;
 pub mod expr;
 pub mod types;
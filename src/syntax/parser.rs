@@ -1,22 +1,62 @@
 use crate::driver::diag::{DiagPrinter, DiagType};
-use super::{ast::{Ast, StatementType}, lexer::Lexer, token::{Token, TokenType}};
-use std::process::exit;
+use super::{ast::{Ast, StatementType}, lexer::Lexer, span::Span, token::{Token, TokenType}};
 
 pub struct Parser {
     lexer: Lexer,
     pub diag: DiagPrinter,
     pub current_token: Token,
+    lookahead: Option<Token>, // Filled by `peek`, taken by the next `consume`
+    previous_span: Span,
+    previous_type: TokenType, // Type of the last consumed token
+    panicking: bool, // Set after a syntax error until the parser resynchronizes
+    pub no_struct_literal: bool, // Set while parsing a `match` scrutinee, so `match x {` doesn't start a struct literal
+    pub namespace_depth: usize, // How many namespace bodies the parser is in
 }
 
 impl Parser {
     pub fn new(mut lexer: Lexer, diag: DiagPrinter) -> Self {
-        let current_token: Token = lexer.next_token();
-        Self { lexer, diag, current_token }
+        let current_token: Token = Self::next_token(&mut lexer);
+        Self { lexer, diag, current_token, lookahead: None, previous_span: Span::default(), previous_type: TokenType::Eof, panicking: false, no_struct_literal: false, namespace_depth: 0 }
+    }
+    // Error tokens were already reported by the lexer, so the parser never sees them
+    fn next_token(lexer: &mut Lexer) -> Token {
+        loop {
+            let token: Token = lexer.next_token();
+            if token.get_type() != TokenType::Error {
+                return token;
+            }
+        }
     }
     pub fn consume(&mut self) -> Token {
-        let current: Token = std::mem::replace(&mut self.current_token, self.lexer.next_token());
+        let next: Token = match self.lookahead.take() {
+            Some(token) => token,
+            None => Self::next_token(&mut self.lexer),
+        };
+        let current: Token = std::mem::replace(&mut self.current_token, next);
+        self.previous_span = current.get_span();
+        self.previous_type = current.get_type();
         current
     }
+    // Consumes the first character of the current token and leaves the rest as a token of type
+    // `rest`, so the `>>` in `a::Array<a::Array<int>>` can close both lists
+    pub fn consume_first_char(&mut self, rest: TokenType) {
+        let span: Span = self.current_token.get_span();
+        let text: String = self.current_token.get_data();
+        self.previous_span = Span { end: span.start + 1, ..span };
+        self.previous_type = TokenType::Greater;
+        self.current_token = Token::new(rest, text[1..].to_string(), Span { start: span.start + 1, column: span.column + 1, ..span });
+    }
+    // Type of the token after `current_token`
+    pub fn peek(&mut self) -> TokenType {
+        if self.lookahead.is_none() {
+            self.lookahead = Some(Self::next_token(&mut self.lexer));
+        }
+        self.lookahead.as_ref().map_or(TokenType::Eof, |token| token.get_type())
+    }
+    // Span from `start` up to and including the last consumed token
+    pub fn span_from(&self, start: Span) -> Span {
+        start.merge(self.previous_span)
+    }
     pub fn expect(&mut self, consume: bool, token_type: TokenType) -> Option<Token> {
         if self.current_token.get_type() == token_type {
             let mut ret_token: Token = self.current_token.clone();
@@ -25,12 +65,59 @@ impl Parser {
             }
             return Some(ret_token);
         }
-        self.diag.print_formatted(DiagType::Error, format!("Expected `{}` but got `{}` instead", token_type as u64, self.current_token.get_data()));
-        exit(1);
+        self.error(self.current_token.get_span(), format!("Expected `{}` but got `{}` instead", token_type.name(), self.current_token.get_data()));
+        None
+    }
+    // Reports a syntax error and enters panic mode, further errors are dropped until the
+    // parser resynchronizes so one mistake doesn't produce a cascade of diagnostics
+    pub fn error(&mut self, span: Span, message: String) {
+        if !self.panicking {
+            self.diag.print_formatted_at(DiagType::Error, span, message);
+        }
+        self.panicking = true;
+    }
+    pub fn is_panicking(&self) -> bool {
+        self.panicking
+    }
+    // Skips to the end of the broken statement starting at `start`: past the next `;`, or up to a `}`
+    // or a declaration. A statement that broke but still reached its own `;` has already ended
+    pub fn synchronize(&mut self, start: Span) {
+        self.panicking = false;
+        if self.previous_type == TokenType::Semicolon && self.previous_span.start >= start.start {
+            return;
+        }
+        loop {
+            match self.current_token.get_type() {
+                TokenType::Semicolon => {
+                    self.consume();
+                    break;
+                }
+                TokenType::CloseCurly | TokenType::Func | TokenType::Namespace | TokenType::Struct | TokenType::Enum | TokenType::Type | TokenType::Eof => break,
+                _ => {
+                    self.consume();
+                }
+            }
+        }
+    }
+    // Skips to the next top level declaration, or the `}` closing the enclosing namespace. Braces
+    // opened while skipping are skipped up to their `}`, like the body of a function whose signature broke
+    pub fn synchronize_top(&mut self) {
+        let mut depth: usize = 0;
+        loop {
+            match self.current_token.get_type() {
+                TokenType::Func | TokenType::Namespace | TokenType::Struct | TokenType::Enum | TokenType::Type | TokenType::Eof => break,
+                TokenType::OpenCurly => depth += 1,
+                TokenType::CloseCurly if depth > 0 => depth -= 1,
+                TokenType::CloseCurly if self.namespace_depth > 0 => break,
+                _ => {}
+            }
+            self.consume();
+        }
+        self.panicking = false;
     }
     pub fn parse_to_ast(&mut self) -> Ast {
         let mut body: Vec<StatementType> = vec![];
-        while self.current_token.get_type() != TokenType::Eof {
+        while self.current_token.get_type() != TokenType::Eof && !self.diag.limit_reached() {
             body.push(self.parse_top_stmt());
         }
         Ast::new(body)
@@ -0,0 +1,23 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: usize, // Byte offset of the first byte
+    pub end: usize,   // Byte offset one past the last byte
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize, line: usize, column: usize) -> Self {
+        Self { start, end, line, column }
+    }
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+    pub fn merge(&self, other: Span) -> Span {
+        let first: Span = if self.start <= other.start { *self } else { other };
+        Span { start: first.start, end: self.end.max(other.end), line: first.line, column: first.column }
+    }
+}
@@ -1,44 +1,130 @@
-use crate::driver::diag::DiagType;
-use super::{ast::{StatementType, BlockStatement}, parser::Parser, token::{TokenType}};
-use std::process::exit;
+use super::{ast::{DeclerationType, ExprType, ForStatement, IfStatement, StatementType, BlockStatement, TypeSpec, WhileStatement}, parser::Parser, span::Span, token::TokenType};
 
 impl Parser {
-    pub fn parse_block_stmt(&mut self) -> BlockStatement {
+    pub fn parse_block_stmt(&mut self, start: Span) -> BlockStatement {
         let mut body: Vec<StatementType> = vec![];
-        while self.current_token.get_type() != TokenType::CloseCurly {
+        // A `func`, `namespace`, `struct`, `enum` or `type` here most likely means the closing `}` is missing
+        while !matches!(self.current_token.get_type(), TokenType::CloseCurly | TokenType::Func | TokenType::Namespace | TokenType::Struct | TokenType::Enum | TokenType::Type | TokenType::Eof) {
             body.push(self.parse_stmt());
         }
         self.expect(true, TokenType::CloseCurly);
-        BlockStatement::new(body)
+        BlockStatement::new(body, self.span_from(start))
+    }
+    // Bodies of control flow statements are always blocks
+    fn parse_body(&mut self) -> StatementType {
+        let start: Span = self.current_token.get_span();
+        if self.expect(true, TokenType::OpenCurly).is_none() {
+            return StatementType::Invalid(start);
+        }
+        StatementType::Block(self.parse_block_stmt(start))
+    }
+    fn parse_condition(&mut self) -> ExprType {
+        self.expect(true, TokenType::OpenParen);
+        let condition: ExprType = self.parse_expr(0);
+        self.expect(true, TokenType::CloseParen);
+        condition
+    }
+    fn parse_if_stmt(&mut self, start: Span) -> StatementType {
+        self.expect(true, TokenType::If);
+        let condition: ExprType = self.parse_condition();
+        let then_branch: StatementType = self.parse_body();
+        let mut else_branch: Option<StatementType> = None;
+        if self.current_token.get_type() == TokenType::Else {
+            self.consume();
+            if self.current_token.get_type() == TokenType::If {
+                let else_start: Span = self.current_token.get_span();
+                else_branch = Some(self.parse_if_stmt(else_start));
+            } else {
+                else_branch = Some(self.parse_body());
+            }
+        }
+        StatementType::If(Box::new(IfStatement::new(condition, then_branch, else_branch, self.span_from(start))))
+    }
+    fn parse_for_stmt(&mut self, start: Span) -> StatementType {
+        self.expect(true, TokenType::For);
+        self.expect(true, TokenType::OpenParen);
+        let Some(variable) = self.expect(true, TokenType::Identifier) else {
+            return StatementType::Invalid(self.span_from(start));
+        };
+        self.expect(true, TokenType::Colon);
+        let type_spec: TypeSpec = self.parse_type_annotation();
+        self.expect(true, TokenType::In);
+        let iterable: ExprType = self.parse_expr(0);
+        self.expect(true, TokenType::CloseParen);
+        let body: StatementType = self.parse_body();
+        StatementType::For(Box::new(ForStatement::new(variable, type_spec, iterable, body, self.span_from(start))))
     }
     pub fn parse_stmt(&mut self) -> StatementType {
+        let start: Span = self.current_token.get_span();
+        let stmt: StatementType = self.parse_stmt_inner(start);
+        if self.is_panicking() {
+            self.synchronize(start);
+            return StatementType::Invalid(self.span_from(start));
+        }
+        stmt
+    }
+    fn parse_stmt_inner(&mut self, start: Span) -> StatementType {
         match self.current_token.get_type() {
             TokenType::OpenCurly => {
                 self.consume();
-                StatementType::Block(self.parse_block_stmt())
+                StatementType::Block(self.parse_block_stmt(start))
+            }
+            TokenType::Var => {
+                let decleration: DeclerationType = self.parse_decleration();
+                self.expect(true, TokenType::Semicolon);
+                StatementType::Decleration(decleration)
+            }
+            TokenType::If => {
+                self.parse_if_stmt(start)
+            }
+            TokenType::While => {
+                self.consume();
+                let condition: ExprType = self.parse_condition();
+                let body: StatementType = self.parse_body();
+                StatementType::While(Box::new(WhileStatement::new(condition, body, self.span_from(start))))
+            }
+            TokenType::For => {
+                self.parse_for_stmt(start)
+            }
+            // A `match` statement ends at its `}`, the `;` after it is optional
+            TokenType::Match => {
+                let expr: ExprType = self.parse_match(start);
+                if self.current_token.get_type() == TokenType::Semicolon {
+                    self.consume();
+                }
+                StatementType::Expr(expr, self.span_from(start))
             }
             TokenType::Return => {
                 self.consume();
-                let stmt: StatementType = StatementType::Return(self.parse_expr(0));
+                let mut expr: Option<ExprType> = None;
+                if self.current_token.get_type() != TokenType::Semicolon {
+                    expr = Some(self.parse_expr(0));
+                }
                 self.expect(true, TokenType::Semicolon);
-                stmt
+                StatementType::Return(expr, self.span_from(start))
             }
             _ => {
-                let stmt: StatementType = StatementType::Expr(self.parse_expr(0));
+                let expr: ExprType = self.parse_expr(0);
                 self.expect(true, TokenType::Semicolon);
-                stmt
+                StatementType::Expr(expr, self.span_from(start))
             }
         }
     }
     pub fn parse_top_stmt(&mut self) -> StatementType {
-        match self.current_token.get_type() {
-            TokenType::Func => {
+        let start: Span = self.current_token.get_span();
+        let stmt: StatementType = match self.current_token.get_type() {
+            TokenType::Func | TokenType::Namespace | TokenType::Struct | TokenType::Enum | TokenType::Type => {
                 StatementType::Decleration(self.parse_decleration())
             }
             _ => {
-                self.diag.print_formatted(DiagType::Error, format!("Unexpected token: `{}`", self.current_token.get_data()));
-                exit(1);
+                self.error(start, format!("Unexpected token: `{}`", self.current_token.get_data()));
+                self.consume();
+                StatementType::Invalid(start)
             }
+        };
+        if self.is_panicking() {
+            self.synchronize_top();
         }
+        stmt
     }
 }
\ No newline at end of file
@@ -1,4 +1,5 @@
 use crate::driver::diag::{DiagPrinter, DiagType};
+use super::span::Span;
 
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
 #[repr(u64)]
@@ -17,45 +18,221 @@ pub enum TokenType {
     Minus,
     MinusMinus,
     MinusEqual,
+    Star,
+    StarEqual,
+    Slash,
+    SlashEqual,
+    Percent,
+    PercentEqual,
+    Equal,
+    EqualEqual,
+    Bang,
+    BangEqual,
+    Less,
+    LessEqual,
+    LessLess,
+    LessLessEqual,
+    Greater,
+    GreaterEqual,
+    GreaterGreater,
+    GreaterGreaterEqual,
+    Ampersand,
+    AmpersandAmpersand,
+    AmpersandEqual,
+    Pipe,
+    PipePipe,
+    PipeEqual,
+    Caret,
+    CaretEqual,
+    Tilde,
+    Dot,
+    Arrow,
     ColonColon,
+    FatArrow,
     Identifier,
     NumericLiteral,
     StringLiteral,
+    CharLiteral,
+    FloatLiteral,
+    Error, // Input the lexer couldn't make sense of, already reported
     __KEYWORDSSTART = 255,
     Func,
+    External,
+    Namespace,
+    Struct,
+    Enum,
+    Type,
+    Var,
     Return,
+    If,
+    Else,
+    While,
+    For,
+    In,
     As,
+    Null,
+    True,
+    False,
+    Match,
     __TYPESSTART = 511,
     Int,
     String,
+    Char,
+    Void,
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+    Bool,
+    F32,
+    F64,
+}
+
+pub const KEYWORDS: &[(&str, TokenType)] = &[
+    ("func", TokenType::Func),
+    ("external", TokenType::External),
+    ("namespace", TokenType::Namespace),
+    ("struct", TokenType::Struct),
+    ("enum", TokenType::Enum),
+    ("type", TokenType::Type),
+    ("var", TokenType::Var),
+    ("int", TokenType::Int),
+    ("string", TokenType::String),
+    ("char", TokenType::Char),
+    ("void", TokenType::Void),
+    ("i8", TokenType::I8),
+    ("i16", TokenType::I16),
+    ("i32", TokenType::I32),
+    ("i64", TokenType::I64),
+    ("u8", TokenType::U8),
+    ("u16", TokenType::U16),
+    ("u32", TokenType::U32),
+    ("u64", TokenType::U64),
+    ("bool", TokenType::Bool),
+    ("f32", TokenType::F32),
+    ("f64", TokenType::F64),
+    ("return", TokenType::Return),
+    ("if", TokenType::If),
+    ("else", TokenType::Else),
+    ("while", TokenType::While),
+    ("for", TokenType::For),
+    ("in", TokenType::In),
+    ("as", TokenType::As),
+    ("null", TokenType::Null),
+    ("true", TokenType::True),
+    ("false", TokenType::False),
+    ("match", TokenType::Match),
+];
+
+// Longer lexmes come before their prefixes so the longest match wins
+pub const PUNCTUATORS: &[(&str, TokenType)] = &[
+    ("<<=", TokenType::LessLessEqual),
+    (">>=", TokenType::GreaterGreaterEqual),
+    ("::", TokenType::ColonColon),
+    ("->", TokenType::Arrow),
+    ("=>", TokenType::FatArrow),
+    ("++", TokenType::PlusPlus),
+    ("--", TokenType::MinusMinus),
+    ("+=", TokenType::PlusEqual),
+    ("-=", TokenType::MinusEqual),
+    ("*=", TokenType::StarEqual),
+    ("/=", TokenType::SlashEqual),
+    ("%=", TokenType::PercentEqual),
+    ("&=", TokenType::AmpersandEqual),
+    ("|=", TokenType::PipeEqual),
+    ("^=", TokenType::CaretEqual),
+    ("==", TokenType::EqualEqual),
+    ("!=", TokenType::BangEqual),
+    ("<=", TokenType::LessEqual),
+    (">=", TokenType::GreaterEqual),
+    ("<<", TokenType::LessLess),
+    (">>", TokenType::GreaterGreater),
+    ("&&", TokenType::AmpersandAmpersand),
+    ("||", TokenType::PipePipe),
+    ("(", TokenType::OpenParen),
+    (")", TokenType::CloseParen),
+    ("{", TokenType::OpenCurly),
+    ("}", TokenType::CloseCurly),
+    (",", TokenType::Comma),
+    (":", TokenType::Colon),
+    (";", TokenType::Semicolon),
+    (".", TokenType::Dot),
+    ("+", TokenType::Plus),
+    ("-", TokenType::Minus),
+    ("*", TokenType::Star),
+    ("/", TokenType::Slash),
+    ("%", TokenType::Percent),
+    ("=", TokenType::Equal),
+    ("!", TokenType::Bang),
+    ("<", TokenType::Less),
+    (">", TokenType::Greater),
+    ("&", TokenType::Ampersand),
+    ("|", TokenType::Pipe),
+    ("^", TokenType::Caret),
+    ("~", TokenType::Tilde),
+];
+
+impl TokenType {
+    // How the token is spelled in source, used when a diagnostic names a token type
+    pub fn name(&self) -> String {
+        for (lexme, token_type) in PUNCTUATORS.iter().chain(KEYWORDS) {
+            if token_type == self {
+                return lexme.to_string();
+            }
+        }
+        match self {
+            TokenType::Eof => "end of file".to_string(),
+            TokenType::Identifier => "identifier".to_string(),
+            TokenType::NumericLiteral => "numeric literal".to_string(),
+            TokenType::StringLiteral => "string literal".to_string(),
+            TokenType::CharLiteral => "character literal".to_string(),
+            TokenType::FloatLiteral => "float literal".to_string(),
+            _ => format!("{:?}", self),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Token {
     token_type: TokenType,
-    value: String,
+    value: String, // Decoded, so escapes in literals are already replaced
+    raw: String, // Exactly as written in the source, including the quotes of literals
+    span: Span,
 }
 
 impl Default for Token {
     fn default() -> Self {
-        Self { token_type: TokenType::Eof, value: "".to_string() }
+        Self { token_type: TokenType::Eof, value: "".to_string(), raw: "".to_string(), span: Span::default() }
     }
 }
 
 impl Token {
-    pub fn new(token_type: TokenType, value: String) -> Self {
-        Self { token_type, value }
+    pub fn new(token_type: TokenType, value: String, span: Span) -> Self {
+        Self { token_type, raw: value.clone(), value, span }
+    }
+    // For literals whose value differs from how they were written
+    pub fn with_raw(token_type: TokenType, value: String, raw: String, span: Span) -> Self {
+        Self { token_type, value, raw, span }
     }
     pub fn print(&self, formatter: &DiagPrinter) {
-        formatter.print_formatted(DiagType::Debug, format!("{} (`{}`)", self.token_type as u64, self.value));
+        formatter.print_formatted(DiagType::Debug, format!("{} (`{}`) at {}:{}", self.token_type as u64, self.value, self.span.line, self.span.column));
     }
     pub fn get_type(&self) -> TokenType {
         self.token_type
     }
+    pub fn get_span(&self) -> Span {
+        self.span
+    }
+    // The token as written, what diagnostics show
     pub fn get_data(&self) -> String {
-        if self.token_type == TokenType::StringLiteral {
-            return '"'.to_string() + &self.value + "\"";
-        }
+        self.raw.clone()
+    }
+    // The decoded value of a literal, the same as `get_data` for everything else
+    pub fn get_value(&self) -> String {
         self.value.clone()
     }
 }
\ No newline at end of file
@@ -1,22 +1,90 @@
-use crate::driver::diag::DiagType;
-use super::{ast::{TypeSpec}, parser::Parser, token::{TokenType}};
-use std::process::exit;
+use super::{ast::{TypeSpec}, expr::can_start_expr, parser::Parser, token::{TokenType}};
 
 impl Parser {
     const TOKENTYPE_AS_TYPESPEC: &[(TokenType, TypeSpec)] = &[
         (TokenType::Int, TypeSpec::Int),
         (TokenType::String, TypeSpec::String),
+        (TokenType::Char, TypeSpec::Char),
+        (TokenType::Void, TypeSpec::Void),
+        (TokenType::I8, TypeSpec::I8),
+        (TokenType::I16, TypeSpec::I16),
+        (TokenType::I32, TypeSpec::I32),
+        (TokenType::I64, TypeSpec::I64),
+        (TokenType::U8, TypeSpec::U8),
+        (TokenType::U16, TypeSpec::U16),
+        (TokenType::U32, TypeSpec::U32),
+        (TokenType::U64, TypeSpec::U64),
+        (TokenType::Bool, TypeSpec::Bool),
+        (TokenType::F32, TypeSpec::F32),
+        (TokenType::F64, TypeSpec::F64),
     ];
     pub fn parse_type_annotation(&mut self) -> TypeSpec {
+        let mut type_spec: TypeSpec = self.parse_base_type();
+        // A `*` followed by an operand multiplies instead, so `x as int * 2` still parses.
+        // Another `*` is taken as part of the type, `x as int * *p` is `(x as int*) * p`
+        while self.current_token.get_type() == TokenType::Star && (self.peek() == TokenType::Star || !can_start_expr(self.peek())) {
+            self.consume();
+            type_spec = TypeSpec::Pointer(Box::new(type_spec));
+        }
+        type_spec
+    }
+    fn parse_base_type(&mut self) -> TypeSpec {
         if self.current_token.get_type() > TokenType::__TYPESSTART {
             for (token_type, type_spec) in Self::TOKENTYPE_AS_TYPESPEC {
                 if *token_type == self.current_token.get_type() {
                     self.consume();
-                    return *type_spec;
+                    return type_spec.clone();
                 }
             }
         }
-        self.diag.print_formatted(DiagType::Error, format!("Expected type specifier, but got `{}` instead", self.current_token.get_data()));
-        exit(1);
+        if self.current_token.get_type() == TokenType::Identifier {
+            return self.parse_named_type();
+        }
+        self.error(self.current_token.get_span(), format!("Expected type specifier, but got `{}` instead", self.current_token.get_data()));
+        TypeSpec::Invalid
+    }
+    // `name` or `namespace::name`, what it refers to is up to the checker. The last segment can
+    // also be a builtin type's keyword, like in `std::string`
+    fn parse_named_type(&mut self) -> TypeSpec {
+        let mut path: String = self.consume().get_data();
+        while self.current_token.get_type() == TokenType::ColonColon {
+            self.consume();
+            if self.current_token.get_type() > TokenType::__TYPESSTART {
+                return TypeSpec::Named(format!("{}::{}", path, self.consume().get_data()));
+            }
+            let Some(segment) = self.expect(true, TokenType::Identifier) else {
+                return TypeSpec::Invalid;
+            };
+            path = format!("{}::{}", path, segment.get_data());
+        }
+        if self.current_token.get_type() == TokenType::Less {
+            return match self.parse_type_arguments() {
+                Some(args) => TypeSpec::Generic(path, args),
+                None => TypeSpec::Invalid,
+            };
+        }
+        TypeSpec::Named(path)
+    }
+    // `<type, ...>` after the name of a generic type
+    fn parse_type_arguments(&mut self) -> Option<Vec<TypeSpec>> {
+        self.consume();
+        let mut args: Vec<TypeSpec> = vec![];
+        loop {
+            args.push(self.parse_type_annotation());
+            if self.current_token.get_type() != TokenType::Comma {
+                break;
+            }
+            self.consume();
+        }
+        // Nested lists can end in `>>`, and `Array<int>=` in `>=`
+        match self.current_token.get_type() {
+            TokenType::GreaterGreater => self.consume_first_char(TokenType::Greater),
+            TokenType::GreaterEqual => self.consume_first_char(TokenType::Equal),
+            TokenType::GreaterGreaterEqual => self.consume_first_char(TokenType::GreaterEqual),
+            _ => {
+                self.expect(true, TokenType::Greater)?;
+            }
+        }
+        Some(args)
     }
-}
\ No newline at end of file
+}
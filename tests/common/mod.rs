@@ -0,0 +1,105 @@
+// Fixtures shared by the integration tests, each test file pulls them in with `mod common;`
+// and not every file uses all of them
+#![allow(dead_code)]
+
+use programming_language::driver::diag::{BufferSink, DiagPrinter};
+use programming_language::sema::checking::sema::SemaChecker;
+use programming_language::syntax::{ast::{Ast, DeclerationType, ExprType, StatementType}, lexer::Lexer, parser::Parser};
+use std::collections::HashMap;
+
+// A printer for `test.comp` holding `source` that writes into the returned sink instead of stderr
+pub fn printer(source: &str, error_limit: usize) -> (DiagPrinter, BufferSink) {
+    let sink: BufferSink = BufferSink::new();
+    let diag: DiagPrinter = DiagPrinter::new(false, false, "test.comp".to_string(), source.to_string(), error_limit).with_sink(Box::new(sink.clone()));
+    (diag, sink)
+}
+
+pub fn lexer(source: &str, diag: &DiagPrinter) -> Lexer {
+    Lexer::new(source.to_string(), diag.clone())
+}
+
+pub fn parser(source: &str, diag: &DiagPrinter) -> Parser {
+    Parser::new(lexer(source, diag), diag.clone())
+}
+
+// The checker after checking `source`, for looking at what it resolved
+pub fn checked(source: &str) -> SemaChecker {
+    let (diag, _) = printer(source, 0);
+    let mut sema: SemaChecker = SemaChecker::new(parser(source, &diag).parse_to_ast(), diag);
+    sema.check();
+    sema
+}
+
+// Where the definition each name used in `source` was resolved to starts, by where the use starts
+pub fn resolved_names(source: &str) -> HashMap<usize, usize> {
+    let (diag, _) = printer(source, 0);
+    let ast: Ast = parser(source, &diag).parse_to_ast();
+    let mut sema: SemaChecker = SemaChecker::new(ast.clone(), diag);
+    sema.check();
+    let mut names: Vec<(usize, usize)> = vec![];
+    for stmt in &ast.body {
+        statement_names(stmt, &mut names);
+    }
+    names.into_iter()
+        .filter_map(|(id, start)| Some((start, sema.definitions[*sema.resolutions.get(&id)?].location?.start)))
+        .collect()
+}
+
+// The id and start of every name used in `stmt`
+fn statement_names(stmt: &StatementType, names: &mut Vec<(usize, usize)>) {
+    match stmt {
+        StatementType::Decleration(DeclerationType::Function(func)) => {
+            if let Some(body) = func.body() {
+                statement_names(&body, names);
+            }
+        }
+        StatementType::Decleration(DeclerationType::Namespace(namespace)) => namespace.body().iter().for_each(|stmt| statement_names(stmt, names)),
+        StatementType::Decleration(DeclerationType::Variable(var)) => {
+            if let Some(initializer) = var.initializer() {
+                expr_names(&initializer, names);
+            }
+        }
+        StatementType::Decleration(_) | StatementType::Invalid(_) | StatementType::Return(None, _) => {}
+        StatementType::Expr(expr, _) | StatementType::Return(Some(expr), _) => expr_names(expr, names),
+        StatementType::Block(block) => block.body.iter().for_each(|stmt| statement_names(stmt, names)),
+        StatementType::If(if_stmt) => {
+            expr_names(&if_stmt.condition(), names);
+            statement_names(&if_stmt.then_branch(), names);
+            if let Some(else_branch) = if_stmt.else_branch() {
+                statement_names(&else_branch, names);
+            }
+        }
+        StatementType::While(while_stmt) => {
+            expr_names(&while_stmt.condition(), names);
+            statement_names(&while_stmt.body(), names);
+        }
+        StatementType::For(for_stmt) => {
+            expr_names(&for_stmt.iterable(), names);
+            statement_names(&for_stmt.body(), names);
+        }
+    }
+}
+
+fn expr_names(expr: &ExprType, names: &mut Vec<(usize, usize)>) {
+    match expr {
+        ExprType::Identifier(_, id, span) => names.push((*id, span.start)),
+        ExprType::Unary(_, operand, _) | ExprType::FieldAccess(operand, _, _) | ExprType::Cast(operand, _, _) => expr_names(operand, names),
+        ExprType::Binary(left, _, right, _) | ExprType::Assign(left, _, right, _) | ExprType::MemberAccess(left, right, _) => {
+            expr_names(left, names);
+            expr_names(right, names);
+        }
+        ExprType::StructLiteral(name, fields, _) => {
+            expr_names(name, names);
+            fields.iter().for_each(|field| expr_names(&field.value, names));
+        }
+        ExprType::Call(callee, args, _) => {
+            expr_names(callee, names);
+            args.iter().for_each(|arg| expr_names(arg, names));
+        }
+        ExprType::Match(match_expr) => {
+            expr_names(&match_expr.scrutinee(), names);
+            match_expr.arms().iter().for_each(|arm| statement_names(&arm.body(), names));
+        }
+        _ => {}
+    }
+}
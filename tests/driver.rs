@@ -0,0 +1,81 @@
+mod common;
+
+use common::printer;
+use programming_language::driver::context::Context;
+use programming_language::driver::diag::{DiagType, Diagnostic};
+use programming_language::syntax::span::Span;
+
+// Everything compiling `source` wrote, reporting at most `error_limit` errors
+fn compile(source: &str, error_limit: usize) -> String {
+    let (diag, sink) = printer(source, error_limit);
+    Context::new(diag, source.to_string()).run();
+    sink.contents()
+}
+
+fn error_lines(output: &str) -> usize {
+    output.lines().filter(|line| line.contains(": Error: ")).count()
+}
+
+#[test]
+fn diagnostics_show_the_source_with_underlines_and_labels() {
+    let source: &str = "func f(): int { return 0; }\nfunc f(): int { return 1; }";
+    let diagnostic: Diagnostic = Diagnostic::new(DiagType::Error, "Redefinition of function `f`".to_string())
+        .with_span(Span::new(33, 34, 2, 6))
+        .with_label(Span::new(5, 6, 1, 6), "previous definition here".to_string());
+    let expected: &str = concat!(
+        "test.comp:2:6: Error: Redefinition of function `f`\n",
+        "1 | func f(): int { return 0; }\n",
+        "  |      - previous definition here\n",
+        "2 | func f(): int { return 1; }\n",
+        "  |      ^\n",
+    );
+    assert_eq!(printer(source, 0).0.render(&diagnostic), expected);
+    // Sema reports exactly that, followed by the summary
+    assert_eq!(compile(source, 0), format!("{}1 error, 0 warnings\n", expected));
+}
+
+#[test]
+fn markers_on_the_same_line_share_it() {
+    let diagnostic: Diagnostic = Diagnostic::new(DiagType::Warning, "Suspicious sum".to_string())
+        .with_span(Span::new(4, 9, 1, 5))
+        .with_label(Span::new(12, 17, 1, 13), "added here".to_string());
+    assert_eq!(printer("x = total + count;", 0).0.render(&diagnostic), concat!(
+        "test.comp:1:5: Warning: Suspicious sum\n",
+        "1 | x = total + count;\n",
+        "  |     ^~~~~\n",
+        "  |             ----- added here\n",
+    ));
+}
+
+#[test]
+fn reporting_stops_at_the_error_limit() {
+    let source: &str = "func f(): int { a; b; c; d; return 0; }";
+    let output: String = compile(source, 2);
+    assert_eq!(error_lines(&output), 2, "{}", output);
+    assert!(output.ends_with("Note: too many errors emitted, stopping now [--error-limit]\n2 errors, 0 warnings\n"), "{}", output);
+    assert_eq!(error_lines(&compile(source, 0)), 4);
+    // The parser stops too, instead of reporting into the void
+    let output: String = compile("func f(): int { ) ; ) ; ) ; return 0; }", 1);
+    assert_eq!(error_lines(&output), 1, "{}", output);
+    assert_eq!(error_lines(&compile("func f(): int { ) ; ) ; ) ; return 0; }", 0)), 3);
+}
+
+#[test]
+fn the_summary_counts_errors_and_warnings() {
+    assert_eq!(compile("func f(): int { return 0; }", 0), "");
+    let (diag, sink) = printer("", 0);
+    diag.print_formatted(DiagType::Error, "first".to_string());
+    diag.print_formatted(DiagType::Warning, "second".to_string());
+    assert!(!diag.finish());
+    assert_eq!(sink.contents(), "Error: first\nWarning: second\n1 error, 1 warning\n");
+    let output: String = compile("func f(): int { a; b; return 0; }", 0);
+    assert!(output.ends_with("\n2 errors, 0 warnings\n"), "{}", output);
+}
+
+#[test]
+fn sema_doesnt_run_after_syntax_errors() {
+    let output: String = compile("func f(): int { undeclared }", 0);
+    assert!(output.contains("test.comp:1:28: Error: Expected"), "{}", output);
+    assert!(!output.contains("undeclared identifier"), "{}", output);
+    assert!(output.ends_with("\n1 error, 0 warnings\n"), "{}", output);
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use common::{parser, printer};
+use programming_language::sema::{analyzer::Sema, hir::{Expr, ExprKind, Function, Hir, Stmt}};
+use programming_language::syntax::{ast::TypeSpec, token::TokenType};
+
+// The typed program for `source`, `None` if it has errors
+fn lower(source: &str) -> Option<Hir> {
+    let (diag, _) = printer(source, 0);
+    Sema::new(parser(source, &diag).parse_to_ast(), diag).run()
+}
+
+fn function<'a>(hir: &'a Hir, name: &str) -> &'a Function {
+    hir.functions.iter().find(|function| function.name == name).unwrap_or_else(|| panic!("no function `{}`", name))
+}
+
+// The statements in the body of `function`
+fn body(function: &Function) -> &[Stmt] {
+    match &function.body {
+        Some(Stmt::Block(body, _)) => body,
+        body => panic!("expected a block, got `{:?}`", body),
+    }
+}
+
+fn returned(stmt: &Stmt) -> &Expr {
+    match stmt {
+        Stmt::Return(Some(expr), _) => expr,
+        stmt => panic!("expected a return, got `{:?}`", stmt),
+    }
+}
+
+fn converted(expr: &Expr) -> &Expr {
+    match &expr.kind {
+        ExprKind::Convert(inner) => inner,
+        kind => panic!("expected a conversion, got `{:?}`", kind),
+    }
+}
+
+#[test]
+fn implicit_conversions_are_explicit() {
+    let hir: Hir = lower("func f(a: u8, b: i32): i64 { return a + b; }").unwrap();
+    let value: &Expr = returned(&body(function(&hir, "f"))[0]);
+    assert_eq!(value.type_spec, TypeSpec::I64);
+    let ExprKind::Binary(left, TokenType::Plus, right) = &converted(value).kind else {
+        panic!("expected an addition, got `{:?}`", value);
+    };
+    assert_eq!((&left.type_spec, &right.type_spec), (&TypeSpec::I32, &TypeSpec::I32));
+    assert_eq!(converted(left).type_spec, TypeSpec::U8);
+    assert!(matches!(right.kind, ExprKind::Variable(_)));
+}
+
+#[test]
+fn literals_take_the_type_they_are_used_as() {
+    let hir: Hir = lower("func f(): void { var x: u8 = 255; var y: i8 = -128; var z: f32 = 1.5; var p: int* = null; }").unwrap();
+    let initializers: Vec<&Expr> = body(function(&hir, "f")).iter().map(|stmt| match stmt {
+        Stmt::Let(_, Some(initializer), _) => initializer,
+        stmt => panic!("expected a `var`, got `{:?}`", stmt),
+    }).collect();
+    assert_eq!(initializers[0].kind, ExprKind::Integer(255));
+    assert_eq!(initializers[0].type_spec, TypeSpec::U8);
+    let ExprKind::Unary(TokenType::Minus, operand) = &initializers[1].kind else {
+        panic!("expected a negation, got `{:?}`", initializers[1]);
+    };
+    assert_eq!((&operand.kind, &operand.type_spec), (&ExprKind::Integer(128), &TypeSpec::I8));
+    assert_eq!((&initializers[2].kind, &initializers[2].type_spec), (&ExprKind::Float(1.5), &TypeSpec::F32));
+    assert_eq!(converted(initializers[3]).kind, ExprKind::Null);
+}
+
+#[test]
+fn names_refer_to_their_definitions() {
+    let hir: Hir = lower("namespace m { func g(x: int): int { return x; } } func f(x: int): int { return m::g(x); }").unwrap();
+    let (g, f): (&Function, &Function) = (function(&hir, "m::g"), function(&hir, "f"));
+    assert_eq!(returned(&body(g)[0]).kind, ExprKind::Variable(g.params[0]));
+    let ExprKind::Call(callee, args) = &returned(&body(f)[0]).kind else {
+        panic!("expected a call in `{:?}`", f);
+    };
+    assert_eq!(*callee, g.definition);
+    assert_eq!(args[0].kind, ExprKind::Variable(f.params[0]));
+    assert_eq!(hir.definitions[f.params[0]].name, "x");
+}
+
+#[test]
+fn conditions_are_bool() {
+    let hir: Hir = lower("func f(n: int): int { while (n && true) { n = n - 1; } if (!n) { return 1; } return 0; }").unwrap();
+    let statements: &[Stmt] = body(function(&hir, "f"));
+    let Stmt::While(condition, _, _) = &statements[0] else {
+        panic!("expected a loop, got `{:?}`", statements[0]);
+    };
+    let ExprKind::Binary(left, TokenType::AmpersandAmpersand, right) = &condition.kind else {
+        panic!("expected `&&`, got `{:?}`", condition);
+    };
+    assert_eq!((&left.type_spec, &converted(left).type_spec), (&TypeSpec::Bool, &TypeSpec::Int));
+    assert_eq!(right.kind, ExprKind::Bool(true));
+    let Stmt::If(condition, _, _, _) = &statements[1] else {
+        panic!("expected an `if`, got `{:?}`", statements[1]);
+    };
+    let ExprKind::Unary(TokenType::Bang, operand) = &condition.kind else {
+        panic!("expected `!`, got `{:?}`", condition);
+    };
+    assert_eq!(converted(operand).type_spec, TypeSpec::Int);
+}
+
+#[test]
+fn programs_with_errors_are_not_lowered() {
+    assert!(lower("func f(): int { return \"a\"; }").is_none());
+    assert!(lower("func f(): int { return 0; }").is_some());
+}
+
+#[test]
+fn matches_bind_and_convert_their_arms() {
+    let hir: Hir = lower("enum Shape { Circle(f64), Square(f32) }
+        func f(s: Shape): f64 { return match s { Shape::Circle(r) => r, Shape::Square(w) => w }; }
+        func g(): Shape { return Shape::Square(2.0); }").unwrap();
+    let ExprKind::Match(_, arms) = &returned(&body(function(&hir, "f"))[0]).kind else {
+        panic!("expected a match");
+    };
+    let Stmt::Expr(square, _) = &arms[1].body else {
+        panic!("expected an expression arm, got `{:?}`", arms[1].body);
+    };
+    assert_eq!(square.type_spec, TypeSpec::F64);
+    assert_eq!(converted(square).type_spec, TypeSpec::F32);
+    let ExprKind::Variant(key, 1, fields) = &returned(&body(function(&hir, "g"))[0]).kind else {
+        panic!("expected a variant");
+    };
+    assert_eq!((key.as_str(), &fields[0].type_spec), ("Shape", &TypeSpec::F32));
+}
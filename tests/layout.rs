@@ -0,0 +1,43 @@
+mod common;
+
+use common::checked;
+use programming_language::sema::checking::{layout::{builtin_layout, Layout}, sema::SemaChecker};
+use programming_language::syntax::ast::TypeSpec;
+
+#[test]
+fn struct_fields_are_aligned_in_declaration_order() {
+    let byte: Layout = Layout::scalar(1, 1);
+    let int: Layout = builtin_layout(&TypeSpec::Int).unwrap();
+    let layout: Layout = Layout::of_struct(&[byte.clone(), int.clone(), byte]);
+    assert_eq!(layout.field_offsets, vec![0, 8, 16]);
+    assert_eq!((layout.size, layout.align), (24, 8));
+}
+
+#[test]
+fn nested_structs_use_their_own_alignment() {
+    let string: Layout = builtin_layout(&TypeSpec::String).unwrap();
+    let inner: Layout = Layout::of_struct(&[string, Layout::scalar(4, 4)]);
+    assert_eq!((inner.size, inner.align), (24, 8));
+    let outer: Layout = Layout::of_struct(&[Layout::scalar(1, 1), inner]);
+    assert_eq!(outer.field_offsets, vec![0, 8]);
+    assert_eq!(outer.size, 32);
+    assert_eq!(Layout::of_struct(&[]), Layout::scalar(0, 1));
+}
+
+#[test]
+fn enums_are_a_tag_followed_by_the_largest_payload() {
+    let int: Layout = builtin_layout(&TypeSpec::Int).unwrap();
+    let layout: Layout = Layout::of_enum(&[Layout::of_struct(&[int.clone(), int]), Layout::of_struct(&[]), Layout::of_struct(&[Layout::scalar(1, 1)])]);
+    assert_eq!(layout.field_offsets, vec![0, 8]);
+    assert_eq!((layout.size, layout.align), (24, 8));
+    assert_eq!(Layout::of_enum(&[Layout::of_struct(&[]), Layout::of_struct(&[])]), Layout { size: 1, align: 1, field_offsets: vec![0, 1] });
+}
+
+#[test]
+fn checked_structs_and_instances_are_laid_out() {
+    let sema: SemaChecker = checked("struct S { a: u8, b: int } struct W<T> { x: u8, y: T } func f(w: W<i16>): int { return 0; }");
+    assert_eq!(sema.layouts["S"], Some(Layout { size: 16, align: 8, field_offsets: vec![0, 8] }));
+    assert_eq!(sema.layouts["W<i16>"], Some(Layout { size: 4, align: 2, field_offsets: vec![0, 2] }));
+    // Only instances of a generic struct have a layout
+    assert!(!sema.layouts.contains_key("W"));
+}
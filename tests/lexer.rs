@@ -0,0 +1,207 @@
+mod common;
+
+use common::{lexer, printer};
+use programming_language::driver::diag::BufferSink;
+use programming_language::syntax::lexer::Lexer;
+use programming_language::syntax::token::{Token, TokenType};
+
+fn lex(source: &str) -> (Vec<Token>, BufferSink) {
+    let (diag, sink) = printer(source, 0);
+    let mut lexer: Lexer = lexer(source, &diag);
+    let mut tokens: Vec<Token> = vec![];
+    loop {
+        let token: Token = lexer.next_token();
+        if token.get_type() == TokenType::Eof {
+            break;
+        }
+        tokens.push(token);
+    }
+    (tokens, sink)
+}
+
+fn lex_types(source: &str) -> Vec<TokenType> {
+    let (tokens, sink) = lex(source);
+    assert_eq!(sink.contents(), "", "unexpected diagnostics for `{}`", source);
+    tokens.iter().map(|token| token.get_type()).collect()
+}
+
+#[test]
+fn lexes_every_operator() {
+    let operators: &[(&str, TokenType)] = &[
+        ("+", TokenType::Plus), ("-", TokenType::Minus), ("*", TokenType::Star), ("/", TokenType::Slash), ("%", TokenType::Percent),
+        ("=", TokenType::Equal), ("==", TokenType::EqualEqual), ("!=", TokenType::BangEqual),
+        ("<", TokenType::Less), ("<=", TokenType::LessEqual), (">", TokenType::Greater), (">=", TokenType::GreaterEqual),
+        ("&&", TokenType::AmpersandAmpersand), ("||", TokenType::PipePipe), ("!", TokenType::Bang),
+        ("&", TokenType::Ampersand), ("|", TokenType::Pipe), ("^", TokenType::Caret), ("~", TokenType::Tilde),
+        ("<<", TokenType::LessLess), (">>", TokenType::GreaterGreater), (".", TokenType::Dot), ("->", TokenType::Arrow), ("=>", TokenType::FatArrow), (",", TokenType::Comma),
+        ("+=", TokenType::PlusEqual), ("-=", TokenType::MinusEqual), ("*=", TokenType::StarEqual), ("/=", TokenType::SlashEqual),
+        ("%=", TokenType::PercentEqual), ("&=", TokenType::AmpersandEqual), ("|=", TokenType::PipeEqual), ("^=", TokenType::CaretEqual),
+        ("<<=", TokenType::LessLessEqual), (">>=", TokenType::GreaterGreaterEqual),
+        ("++", TokenType::PlusPlus), ("--", TokenType::MinusMinus),
+    ];
+    for (lexme, token_type) in operators {
+        let (tokens, _) = lex(lexme);
+        assert_eq!(tokens.len(), 1, "`{}` should be a single token", lexme);
+        assert_eq!(tokens[0].get_type(), *token_type, "`{}`", lexme);
+        assert_eq!(tokens[0].get_data(), *lexme);
+    }
+}
+
+#[test]
+fn takes_the_longest_operator() {
+    assert_eq!(lex_types("a<<=b"), vec![TokenType::Identifier, TokenType::LessLessEqual, TokenType::Identifier]);
+    assert_eq!(lex_types("a->b"), vec![TokenType::Identifier, TokenType::Arrow, TokenType::Identifier]);
+    assert_eq!(lex_types("a---b"), vec![TokenType::Identifier, TokenType::MinusMinus, TokenType::Minus, TokenType::Identifier]);
+    assert_eq!(lex_types("a- -b"), vec![TokenType::Identifier, TokenType::Minus, TokenType::Minus, TokenType::Identifier]);
+    assert_eq!(lex_types("!!a"), vec![TokenType::Bang, TokenType::Bang, TokenType::Identifier]);
+}
+
+#[test]
+fn lexes_call_arguments() {
+    assert_eq!(lex_types("std::println(\"a\", b);"), vec![
+        TokenType::Identifier, TokenType::ColonColon, TokenType::Identifier, TokenType::OpenParen,
+        TokenType::StringLiteral, TokenType::Comma, TokenType::Identifier, TokenType::CloseParen, TokenType::Semicolon,
+    ]);
+}
+
+#[test]
+fn slash_is_not_a_comment() {
+    assert_eq!(lex_types("a / b // c / d\n/ e"), vec![TokenType::Identifier, TokenType::Slash, TokenType::Identifier, TokenType::Slash, TokenType::Identifier]);
+}
+
+#[test]
+fn tracks_spans_across_utf8() {
+    let (tokens, _) = lex("\"é\" ->\n  x");
+    assert_eq!(tokens[1].get_span().start, 5);
+    assert_eq!((tokens[1].get_span().line, tokens[1].get_span().column), (1, 5));
+    assert_eq!((tokens[2].get_span().line, tokens[2].get_span().column), (2, 3));
+}
+
+#[test]
+fn reports_unknown_characters_and_keeps_going() {
+    let (tokens, sink) = lex("a $ b");
+    let types: Vec<TokenType> = tokens.iter().map(|token| token.get_type()).collect();
+    assert_eq!(types, vec![TokenType::Identifier, TokenType::Error, TokenType::Identifier]);
+    assert!(sink.contents().contains("Unknown token `$`"));
+}
+
+#[test]
+fn decodes_char_literals() {
+    let (tokens, sink) = lex(r"'a' '\n' '\'' '\\' 'é'");
+    assert_eq!(sink.contents(), "");
+    let values: Vec<String> = tokens.iter().map(|token| token.get_data()).collect();
+    assert_eq!(values, vec!["'a'", "'\\n'", "'\\''", "'\\\\'", "'é'"]);
+    assert!(tokens.iter().all(|token| token.get_type() == TokenType::CharLiteral));
+}
+
+#[test]
+fn reports_malformed_char_literals() {
+    for (source, message) in [("''", "Empty character literal"), ("'ab'", "may only contain one character"), ("'a", "Unterminated character literal"), (r"'\q'", "Unknown escape sequence `\\q`")] {
+        let (tokens, sink) = lex(source);
+        assert_eq!(tokens[0].get_type(), TokenType::CharLiteral, "`{}`", source);
+        assert!(sink.contents().contains(message), "`{}` reported `{}`", source, sink.contents());
+    }
+}
+
+#[test]
+fn decodes_string_escapes_and_keeps_the_raw_text() {
+    let source: &str = r#""a\tb\n\r\0\\\"\'\x41\u{e9}\u{1F600}""#;
+    let (tokens, sink) = lex(source);
+    assert_eq!(sink.contents(), "");
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0].get_value(), "a\tb\n\r\0\\\"'A\u{e9}\u{1F600}");
+    assert_eq!(tokens[0].get_data(), source);
+}
+
+// Column of the first diagnostic, which should point at the offending character
+fn error_column(source: &str) -> String {
+    let (_, sink) = lex(source);
+    let contents: String = sink.contents();
+    contents.split(':').nth(2).unwrap_or_default().to_string()
+}
+
+#[test]
+fn points_at_the_bad_character_of_an_escape() {
+    assert_eq!(error_column(r#""ab\qc""#), "5");
+    assert_eq!(error_column(r#""\x4g""#), "5");
+    assert_eq!(error_column(r#""\x""#), "4");
+    assert_eq!(error_column(r#""\u{12z}""#), "7");
+    assert_eq!(error_column(r#""\u12""#), "4");
+    assert_eq!(error_column(r#""\u{D800}""#), "5");
+    assert_eq!(error_column(r#""\u{1234567}""#), "11");
+    assert!(lex(r#""\x80""#).1.contents().contains("out of range"));
+}
+
+#[test]
+fn bad_escapes_dont_end_the_string() {
+    let (tokens, sink) = lex(r#""\q" x"#);
+    assert!(sink.contents().contains("Unknown escape sequence `\\q`"));
+    let types: Vec<TokenType> = tokens.iter().map(|token| token.get_type()).collect();
+    assert_eq!(types, vec![TokenType::StringLiteral, TokenType::Identifier]);
+}
+
+#[test]
+fn lexes_prefixed_and_separated_integers() {
+    use programming_language::syntax::lexer::integer_literal_value;
+    let (tokens, sink) = lex("0xFF_ff 0b1010 0o17 1_000 7int 0");
+    assert_eq!(sink.contents(), "");
+    let values: Vec<(Option<u128>, String)> = tokens.iter().map(|token| {
+        let text: String = token.get_data();
+        let (value, suffix) = integer_literal_value(&text);
+        (value, suffix.to_string())
+    }).collect();
+    assert_eq!(values, vec![
+        (Some(0xFFFF), "".to_string()), (Some(10), "".to_string()), (Some(15), "".to_string()),
+        (Some(1000), "".to_string()), (Some(7), "int".to_string()), (Some(0), "".to_string()),
+    ]);
+    assert_eq!(integer_literal_value("340282366920938463463374607431768211456").0, None);
+}
+
+#[test]
+fn reports_bad_digits_in_prefixed_integers() {
+    assert!(lex("0b012").1.contents().contains("Invalid digit `2` in binary literal"));
+    assert!(lex("0o8").1.contents().contains("Invalid digit `8` in octal literal"));
+    assert!(lex("0x").1.contents().contains("Missing digits in hexadecimal literal"));
+}
+
+#[test]
+fn sized_types_and_booleans_are_keywords() {
+    assert_eq!(lex_types("i8 u64 bool true false truth"), vec![TokenType::I8, TokenType::U64, TokenType::Bool, TokenType::True, TokenType::False, TokenType::Identifier]);
+}
+
+#[test]
+fn lexes_float_literals() {
+    use programming_language::syntax::lexer::float_literal_value;
+    let (tokens, sink) = lex("1.5 2e10 1E-3 0x1p3 0x1.8p1 3f32 1_0.2_5e+1f64");
+    assert_eq!(sink.contents(), "");
+    assert!(tokens.iter().all(|token| token.get_type() == TokenType::FloatLiteral));
+    let values: Vec<(Option<f64>, String)> = tokens.iter().map(|token| {
+        let text: String = token.get_data();
+        let (value, suffix) = float_literal_value(&text);
+        (value, suffix.to_string())
+    }).collect();
+    assert_eq!(values, vec![
+        (Some(1.5), "".to_string()), (Some(2e10), "".to_string()), (Some(0.001), "".to_string()), (Some(8.0), "".to_string()),
+        (Some(3.0), "".to_string()), (Some(3.0), "f32".to_string()), (Some(102.5), "f64".to_string()),
+    ]);
+}
+
+#[test]
+fn a_dot_without_digits_is_not_a_fraction() {
+    assert_eq!(lex_types("1.x 0x1e5 2else"), vec![TokenType::NumericLiteral, TokenType::Dot, TokenType::Identifier, TokenType::NumericLiteral, TokenType::NumericLiteral]);
+    assert!(lex("0x1.8").1.contents().contains("needs a `p` exponent"));
+    assert!(lex("1e+").1.contents().contains("Missing digits in exponent"));
+}
+
+#[test]
+fn the_cursor_steps_over_every_character_once() {
+    let unit: &str = "// générée\nfunc f(): int {\n    std::println(\"Grüße\\n\", 'x');\n    return 1_000 + 0x2A; // fin\n}\n";
+    for repeats in [1, 10, 1000] {
+        let source: String = unit.repeat(repeats);
+        let (diag, _) = printer(&source, 0);
+        let mut lexer: Lexer = lexer(&source, &diag);
+        while lexer.next_token().get_type() != TokenType::Eof {}
+        assert!(!diag.has_errors());
+        assert_eq!(lexer.steps(), source.chars().count());
+    }
+}
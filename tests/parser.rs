@@ -0,0 +1,219 @@
+mod common;
+
+use common::{parser, printer};
+use programming_language::syntax::ast::{Ast, DeclerationType, ExprType, Pattern, StatementType};
+
+// An expression and the diagnostics parsing it produced
+fn parse_expr_reporting(source: &str) -> (ExprType, String) {
+    let (diag, sink) = printer(source, 0);
+    let expr: ExprType = parser(source, &diag).parse_expr(0);
+    (expr, sink.contents())
+}
+
+fn parse_expr(source: &str) -> ExprType {
+    let (expr, diagnostics) = parse_expr_reporting(source);
+    assert_eq!(diagnostics, "", "unexpected diagnostics for `{}`", source);
+    expr
+}
+
+// Parses a whole file, diagnostics are returned instead of checked
+fn parse_program(source: &str) -> (Ast, String, usize) {
+    let (diag, sink) = printer(source, 0);
+    let ast: Ast = parser(source, &diag).parse_to_ast();
+    (ast, sink.contents(), diag.error_count())
+}
+
+// Statements in the body of the function declared by `stmt`
+fn function_body(stmt: &StatementType) -> Vec<StatementType> {
+    match stmt {
+        StatementType::Decleration(DeclerationType::Function(func)) => match func.body() {
+            Some(StatementType::Block(block)) => block.body,
+            body => panic!("expected a block body, got {:?}", body),
+        },
+        _ => panic!("expected a function, got {:?}", stmt),
+    }
+}
+
+// Fully parenthesized form of an expression so the tree shape is easy to compare
+fn show(expr: &ExprType) -> String {
+    match expr {
+        ExprType::Identifier(token, _, _) | ExprType::NumericLiteral(token, _) | ExprType::FloatLiteral(token, _) | ExprType::StringLiteral(token, _) | ExprType::NullLiteral(token, _) | ExprType::CharLiteral(token, _) | ExprType::BoolLiteral(token, _) => token.get_data(),
+        ExprType::Unary(op, operand, _) => format!("({}{})", op.get_data(), show(operand)),
+        ExprType::Binary(left, op, right, _) | ExprType::Assign(left, op, right, _) => format!("({} {} {})", show(left), op.get_data(), show(right)),
+        ExprType::Cast(value, type_spec, _) => format!("({} as {:?})", show(value), type_spec),
+        ExprType::Call(callee, args, _) => format!("{}({})", show(callee), args.iter().map(show).collect::<Vec<String>>().join(", ")),
+        ExprType::MemberAccess(member, property, _) => format!("{}::{}", show(member), show(property)),
+        ExprType::FieldAccess(value, field, _) => format!("{}.{}", show(value), field.get_data()),
+        ExprType::StructLiteral(name, fields, _) => format!("{} {{ {} }}", show(name), fields.iter().map(|field| format!("{}: {}", field.name.get_data(), show(&field.value))).collect::<Vec<String>>().join(", ")),
+        ExprType::Match(match_expr) => {
+            let arms: Vec<String> = match_expr.arms().iter().map(|arm| format!("{} => {}", show_pattern(&arm.pattern()), match arm.body() {
+                StatementType::Expr(expr, _) => show(&expr),
+                _ => "{ ... }".to_string(),
+            })).collect();
+            format!("match {} {{ {} }}", show(&match_expr.scrutinee()), arms.join(", "))
+        }
+        ExprType::Invalid(_) => "<invalid>".to_string(),
+    }
+}
+
+fn show_pattern(pattern: &Pattern) -> String {
+    match pattern {
+        Pattern::Wildcard(_) => "_".to_string(),
+        Pattern::Binding(name, _) => format!("${}", name.get_data()),
+        Pattern::Literal(literal, _) => show(literal),
+        Pattern::Variant(path, fields, _) if fields.is_empty() => show(path),
+        Pattern::Variant(path, fields, _) => format!("{}({})", show(path), fields.iter().map(show_pattern).collect::<Vec<String>>().join(", ")),
+    }
+}
+
+fn parses_to(source: &str, expected: &str) {
+    assert_eq!(show(&parse_expr(source)), expected, "`{}`", source);
+}
+
+#[test]
+fn binary_operators_are_left_associative() {
+    parses_to("a - b - c", "((a - b) - c)");
+    parses_to("a / b * c % d", "(((a / b) * c) % d)");
+    parses_to("a << b >> c", "((a << b) >> c)");
+}
+
+#[test]
+fn assignment_is_right_associative() {
+    parses_to("a = b = c", "(a = (b = c))");
+    parses_to("a += b -= c", "(a += (b -= c))");
+}
+
+#[test]
+fn precedence_levels() {
+    parses_to("a + b * c", "(a + (b * c))");
+    parses_to("a + b << c", "((a + b) << c)");
+    parses_to("a << b < c", "((a << b) < c)");
+    parses_to("a < b == c", "((a < b) == c)");
+    parses_to("a & b ^ c | d", "(((a & b) ^ c) | d)");
+    parses_to("a == b && c || d", "(((a == b) && c) || d)");
+    parses_to("a = b || c", "(a = (b || c))");
+    parses_to("(a + b) * c", "((a + b) * c)");
+}
+
+#[test]
+fn prefix_operators_bind_tightest() {
+    parses_to("-a * b", "((-a) * b)");
+    parses_to("!a && ~b", "((!a) && (~b))");
+    parses_to("*a + &b", "((*a) + (&b))");
+    parses_to("- -a", "(-(-a))");
+    parses_to("-f(a)", "(-f(a))");
+}
+
+#[test]
+fn cast_is_a_postfix_precedence_operator() {
+    parses_to("-x as int * 2", "(((-x) as Int) * 2)");
+    parses_to("a + b as int", "(a + (b as Int))");
+    parses_to("a as int as string", "((a as Int) as String)");
+    parses_to("a = b as int", "(a = (b as Int))");
+}
+
+#[test]
+fn assignment_operators_build_assign_nodes() {
+    assert!(matches!(parse_expr("a = b + c"), ExprType::Assign(..)));
+    assert!(matches!(parse_expr("a <<= b"), ExprType::Assign(..)));
+    assert!(matches!(parse_expr("a == b"), ExprType::Binary(..)));
+}
+
+#[test]
+fn field_access_and_struct_literals_are_postfix() {
+    parses_to("a.b.c", "a.b.c");
+    parses_to("-a.b * c", "((-a.b) * c)");
+    parses_to("f(a).b = c", "(f(a).b = c)");
+    parses_to("geo::Point { x: 1, y: a + b, }", "geo::Point { x: 1, y: (a + b) }");
+    parses_to("Empty {}.x", "Empty {  }.x");
+}
+
+#[test]
+fn star_after_a_cast_type_is_a_pointer_unless_an_operand_follows() {
+    parses_to("x as int* + 1", "((x as Pointer(Int)) + 1)");
+    parses_to("x as int** == null", "((x as Pointer(Pointer(Int))) == null)");
+    parses_to("x as int * 2", "((x as Int) * 2)");
+    parses_to("x as int * -y", "((x as Int) * (-y))");
+    parses_to("x as int * *p", "((x as Pointer(Int)) * p)");
+}
+
+#[test]
+fn generic_type_arguments_nest() {
+    parses_to("x as std::Array<int>*", "(x as Pointer(Generic(\"std::Array\", [Int])))");
+    parses_to("x as Pair<u8, Box<Box<int>>> == y", "((x as Generic(\"Pair\", [U8, Generic(\"Box\", [Generic(\"Box\", [Int])])])) == y)");
+}
+
+#[test]
+fn match_arms_take_patterns_and_expression_or_block_bodies() {
+    parses_to("match s { Shape::Circle(r) => r * 2, Shape::Rect(_, -1) => 0, Shape::Empty => { }, other => 1 }",
+        "match s { Shape::Circle($r) => (r * 2), Shape::Rect(_, (-1)) => 0, Shape::Empty => { ... }, $other => 1 }");
+    parses_to("1 + match x { 'a' => 2 }", "(1 + match x { 'a' => 2 })");
+}
+
+#[test]
+fn match_scrutinee_is_never_a_struct_literal() {
+    parses_to("match p { _ => 0 }", "match p { _ => 0 }");
+    parses_to("match (Point { x: 1 }).x { _ => 0 }", "match Point { x: 1 }.x { _ => 0 }");
+    parses_to("match f(Point { x: 1 }) { _ => 0 }", "match f(Point { x: 1 }) { _ => 0 }");
+}
+
+#[test]
+fn separate_syntax_errors_are_all_reported() {
+    let (ast, diagnostics, errors) = parse_program("func f(): int { a + ; return 0; }\nfunc g(): int { ) ; return 1; }");
+    assert_eq!(diagnostics, concat!(
+        "test.comp:1:21: Error: Unexpected token: `;`\n",
+        "1 | func f(): int { a + ; return 0; }\n",
+        "  |                     ^\n",
+        "test.comp:2:17: Error: Unexpected token: `)`\n",
+        "2 | func g(): int { ) ; return 1; }\n",
+        "  |                 ^\n",
+    ));
+    assert_eq!(errors, 2);
+    assert_eq!(ast.body.len(), 2);
+}
+
+#[test]
+fn an_unknown_character_is_skipped_by_the_parser() {
+    let (ast, diagnostics, errors) = parse_program("func f(): int { $ return 0; }");
+    assert_eq!(diagnostics, concat!(
+        "test.comp:1:17: Error: Unknown token `$`\n",
+        "1 | func f(): int { $ return 0; }\n",
+        "  |                 ^\n",
+    ));
+    assert_eq!(errors, 1);
+    assert!(matches!(function_body(&ast.body[0]).as_slice(), [StatementType::Return(_, _)]));
+}
+
+#[test]
+fn failed_constructs_become_invalid_nodes() {
+    let (ast, diagnostics, errors) = parse_program("return 1;\nfunc f(): int { a + ; return 0; }");
+    assert_eq!(diagnostics, concat!(
+        "test.comp:1:1: Error: Unexpected token: `return`\n",
+        "1 | return 1;\n",
+        "  | ^~~~~~\n",
+        "test.comp:2:21: Error: Unexpected token: `;`\n",
+        "2 | func f(): int { a + ; return 0; }\n",
+        "  |                     ^\n",
+    ));
+    assert_eq!(errors, 2);
+    assert!(matches!(ast.body[0], StatementType::Invalid(_)));
+    assert!(matches!(function_body(&ast.body[1]).as_slice(), [StatementType::Invalid(_), StatementType::Return(_, _)]));
+    // A loop without a variable is dropped as a whole
+    let (ast, diagnostics, errors) = parse_program("func f(v: int): int { for (: int in v) return 0; var x: int = 1; }");
+    assert_eq!(errors, 1, "{}", diagnostics);
+    assert!(diagnostics.contains("Expected `identifier` but got `:` instead"), "{}", diagnostics);
+    assert!(matches!(function_body(&ast.body[0]).as_slice(), [StatementType::Invalid(_), StatementType::Decleration(_)]));
+    // A broken operand is an invalid node in an otherwise complete tree
+    let (expr, diagnostics) = parse_expr_reporting("a + )");
+    assert!(matches!(expr, ExprType::Binary(_, _, ref right, _) if matches!(**right, ExprType::Invalid(_))), "{:?}", expr);
+    assert!(diagnostics.contains("Error: Unexpected token: `)`"), "{}", diagnostics);
+}
+
+#[test]
+fn a_broken_signature_is_reported_once() {
+    let (_, diagnostics, errors) = parse_program("func f(x int): int { if (x) { return 1; } return 0; }\nfunc g(): int { return 0; }");
+    assert_eq!(errors, 1, "{}", diagnostics);
+    assert!(diagnostics.contains("Expected `:` but got `int` instead"), "{}", diagnostics);
+    let (_, diagnostics, errors) = parse_program("namespace a { func f(x int): int { if (x) { return 1; } return 0; } func g(): int { return 0; } }\nfunc h(): int { return 0; }");
+    assert_eq!(errors, 1, "{}", diagnostics);
+}
@@ -0,0 +1,278 @@
+mod common;
+
+use common::{printer, resolved_names};
+use programming_language::driver::context::Context;
+use std::collections::HashMap;
+
+// Diagnostics from checking `source`
+fn check(source: &str) -> String {
+    let (diag, sink) = printer(source, 0);
+    Context::new(diag, source.to_string()).run();
+    sink.contents()
+}
+
+fn assert_clean(source: &str) {
+    assert_eq!(check(source), "", "unexpected diagnostics for `{}`", source);
+}
+
+fn assert_reports(source: &str, message: &str) {
+    let contents: String = check(source);
+    assert!(contents.contains(message), "expected `{}` for `{}`, got `{}`", message, source, contents);
+}
+
+#[test]
+fn call_arguments_are_checked_against_the_parameters() {
+    let g: &str = "func g(a: int, s: string): int { return a; }";
+    assert_clean(&format!("{} func f(): int {{ return g(1, \"x\"); }}", g));
+    assert_reports(&format!("{} func f(): int {{ g(1); }}", g), "Function `g` expects 2 arguments but 1 was given");
+    assert_reports(&format!("{} func f(): int {{ g(1, \"x\", 3); }}", g), "Function `g` expects 2 arguments but 3 were given");
+    let contents: String = check(&format!("{} func f(): int {{ g(\"x\", 1); }}", g));
+    assert!(contents.contains("Mismatched argument type, expected `int` but got `string`"), "{}", contents);
+    assert!(contents.contains("Mismatched argument type, expected `string` but got `int`"), "{}", contents);
+    assert_reports("func f(x: int): int { x(1); }", "`x` is not a function");
+}
+
+#[test]
+fn variables_are_visible_after_their_declaration() {
+    assert_clean("func f(): int { var x: int = 1; var y: int = x; y = x + y; return y; }");
+    assert_reports("func f(): int { x = 1; var x: int = 2; }", "Use of variable `x` before its declaration");
+    assert_reports("func f(): int { var x: int = x; }", "Use of variable `x` before its declaration");
+    assert_reports("func f(): int { var x: int = 1; var x: int = 2; }", "Redeclaration of variable `x` in the same scope");
+    assert_reports("func f(): int { var x: int = \"one\"; }", "Mismatched types, variable `x` is declared as `int` but initialized with `string`");
+    // The initializer of a variable that shadows another one still sees the shadowed one
+    let source: &str = "func f(x: int): int { { var x: int = x + 1; return x; } }";
+    let names: HashMap<usize, usize> = resolved_names(source);
+    assert_eq!(names.get(&source.find("x + 1").unwrap()).copied(), source.find("x: int)"));
+    assert_eq!(names.get(&source.find("x; }").unwrap()).copied(), source.find("x: int = x"));
+}
+
+#[test]
+fn conditions_and_loop_variables_are_checked() {
+    // Integers compare with zero, anything else is an error
+    assert_clean("func f(x: int): int { if (x) { x = 1; } while (x) { x = x - 1; } return x; }");
+    assert_reports("func f(s: string): int { if (s) { } }", "Condition must have a boolean-compatible type, but has type `string`");
+    assert_reports("func f(s: string): int { while (s) { } }", "Condition must have a boolean-compatible type, but has type `string`");
+    assert_reports("func f(x: int): int { for (s: int in x) { } }", "Type `int` can't be iterated over");
+}
+
+#[test]
+fn struct_fields_are_checked() {
+    let s: &str = "struct S { a: int, b: int }";
+    assert_clean(&format!("{} func f(): int {{ var s: S = S {{ b: 2, a: 1 }}; var x: int = s.a + s.b; return x; }}", s));
+    assert_reports(&format!("{} func f(s: S): int {{ var x: int = s.c; }}", s), "No field named `c` in struct `S`");
+    assert_reports(&format!("{} func f(): int {{ var s: S = S {{ a: 1, b: 2, c: 3 }}; }}", s), "No field named `c` in struct `S`");
+    assert_reports(&format!("{} func f(): int {{ var s: S = S {{ a: 1 }}; }}", s), "Missing field `b` in initializer of struct `S`");
+    assert_reports(&format!("{} func f(): int {{ var s: S = S {{ a: 1, a: 2, b: 3 }}; }}", s), "Field `a` is initialized more than once");
+    assert_reports(&format!("{} func f(s: S): int {{ s(); }}", s), "`s` is not a function");
+    assert_reports("struct A { a: A }", "Recursive struct `A` has infinite size");
+    assert_reports("struct A { b: B } struct B { a: A }", "Recursive struct `A` has infinite size");
+}
+
+#[test]
+fn pointer_operators_are_checked() {
+    assert_clean("func f(x: int): int { var p: int* = &x; p = p + 1; var y: int = *p; return y; }");
+    assert_reports("func f(x: int): int { var y: int = *x; }", "Cannot dereference a value of type `int`, only pointers can be dereferenced");
+    assert_reports("func f(): int { var p: int* = &1; }", "Cannot take the address of a temporary value");
+    assert_reports("func f(x: int): int { var p: int* = &(x + 1); }", "Cannot take the address of a temporary value");
+    assert_reports("func f(p: int*): int { var q: int* = p * 2; }", "Invalid operands of types `int*` and `int` to binary operator (`*`)");
+    assert_reports("func f(p: int*, q: int*): int { var r: int* = p + q; }", "Invalid operands of types `int*` and `int*` to binary operator (`+`)");
+    assert_reports("func f(p: int*): int { var x: int = p + 1; }", "Mismatched types, variable `x` is declared as `int` but initialized with `int*`");
+    // A struct can only contain itself through a pointer
+    assert_clean("struct List { next: List*, value: int }");
+}
+
+#[test]
+fn returns_match_the_return_type_and_void_is_only_a_return_type() {
+    assert_clean("func g(): void { return; } func f(): int { g(); return 0; }");
+    assert_reports("func f(): void { return 1; }", "Function `f` returns `void` and can't return a value");
+    assert_reports("func f(): int { return; }", "Function `f` must return a value of type `int`");
+    assert_reports("func f(): int { var x: void; return 0; }", "`void` is only valid as a return type");
+    assert_reports("func f(x: void): int { return 0; }", "`void` is only valid as a return type");
+}
+
+#[test]
+fn unsuffixed_literals_take_the_expected_type() {
+    assert_clean("func f(a: u8): u16 { var x: u8 = 255; var y: i8 = -128; return a + 1; }");
+    assert_reports("func f(): int { var x: u8 = 256; return 0; }", "Literal out of range for type `u8`, which holds `0..=255`");
+    assert_reports("func f(): int { var x: f32 = 1e39; return 0; }", "Literal out of range for type `f32`");
+}
+
+#[test]
+fn casts_are_checked() {
+    assert_clean("func f(p: int*, b: bool, c: f64): int { return p as int + b as int + c as int; }");
+    assert_reports("func f(): int { return \"abc\" as int; }", "Cannot cast a value of type `string` to `int`");
+    assert_reports("func f(): bool { return 1 as bool; }", "compare with `0` instead");
+}
+
+#[test]
+fn generic_structs_are_instantiated_per_argument_list() {
+    assert_clean("struct Pair<A, B> { first: A, second: B }
+        func f(p: Pair<int, string>, q: Pair<u8, u8>): int { var s: string = p.second; var b: u8 = q.first; return p.first; }");
+    assert_reports("struct Pair<A, B> { first: A, second: B } func f(p: Pair<int, string>): int { return p.second; }", "expected `int` but got `string`");
+    assert_reports("struct Pair<A, B> { first: A, second: B } func f(p: Pair<int>): int { return 0; }", "Struct `Pair` expects 2 type arguments but 1 was given");
+    assert_reports("struct Point { x: int } func f(p: Point<int>): int { return 0; }", "Struct `Point` isn't generic");
+}
+
+#[test]
+fn runaway_and_recursive_instances_are_reported() {
+    assert_reports("struct W<T> { inner: W<W<T>>* } func f(w: W<int>): int { return 0; }", "Generic struct `W` instantiates itself with ever deeper type arguments");
+    assert_reports("struct Box<T> { value: T } struct A { b: Box<A> }", "Recursive struct `A` has infinite size");
+    assert_clean("struct Node<T> { value: T, next: Node<T>* } func f(n: Node<int>): int { return (*n.next).value; }");
+}
+
+#[test]
+fn arrays_can_be_iterated() {
+    assert_clean("func f(values: std::Array<int>): int { var total: int = 0; for (value: int in values) { total = total + value; } return total; }");
+    assert_reports("func f(values: std::Array<int>): int { for (value: u8 in values) {} return 0; }", "loop variable `value` is declared as `u8` but the elements are `int`");
+}
+
+#[test]
+fn the_documented_main_signature_checks() {
+    assert_clean("func main(var args: std::Array<std::string>): int { for (arg: std::string in args) { var s: string = arg; } return 0; }");
+    assert_reports("func f(x: std::int): int { return 0; }", "Unknown type `std::int`");
+}
+
+#[test]
+fn aliases_are_the_type_they_stand_for() {
+    assert_clean("type Meters = int; namespace geo { struct Point { x: Meters } type P = Point; }
+        func f(m: Meters, p: geo::P): int { var q: geo::P = geo::P { x: m }; return p.x + m; }");
+    assert_clean("struct Pair<A, B> { first: A, second: B } type IntPair = Pair<int, int>; func f(): int { var p: IntPair = IntPair { first: 1, second: 2 }; return p.first; }");
+    assert_reports("type Meters = int; func f(m: Meters): int { var s: string = m; return 0; }", "initialized with `Meters (aka int)`");
+    assert_reports("type Name = string; func f(p: Name*): int { var q: int* = p; return 0; }", "initialized with `Name* (aka string*)`");
+}
+
+#[test]
+fn alias_cycles_are_reported_once() {
+    let contents: String = check("type A = B; type B = A*; func f(a: A): int { return 0; }");
+    assert_eq!(contents.matches("refers to itself").count(), 1, "{}", contents);
+    assert!(contents.contains("`A` refers to `B` here") && contents.contains("`B` refers to `A` here"), "{}", contents);
+    assert_reports("type Loop = Loop*;", "`Loop` refers to itself here");
+}
+
+const SHAPE: &str = "enum Shape { Circle(f64), Rect(f64, f64), Empty }";
+
+#[test]
+fn enum_variants_are_built_and_matched() {
+    assert_clean(&format!("{} func area(s: Shape): f64 {{
+        var unit: Shape = Shape::Circle(1.0);
+        var none: Shape = Shape::Empty;
+        match s {{
+            Shape::Circle(r) => {{ var d: f64 = r * 2.0; }}
+            _ => {{}}
+        }}
+        return match s {{ Shape::Circle(r) => r * r * 3.14, Shape::Rect(w, h) => w * h, Shape::Empty => 0.0 }};
+    }}", SHAPE));
+    assert_clean("enum Level { Low, High } type L = Level; func f(l: L): u8 { var x: u8 = match l { L::Low => 1, Level::High => 255 }; return x; }");
+    assert_reports(&format!("{} func f(): int {{ var s: Shape = Shape::Circle; return 0; }}", SHAPE), "Variant `Shape::Circle` carries data");
+    assert_reports(&format!("{} func f(): int {{ var s: Shape = Shape::Rect(1.0); return 0; }}", SHAPE), "Variant `Shape::Rect` has 2 fields but 1 was given");
+    assert_reports(&format!("{} func f(): int {{ var s: Shape = Shape::Square; return 0; }}", SHAPE), "No variant named `Square` in enum `Shape`");
+}
+
+#[test]
+fn non_exhaustive_matches_list_what_is_missing() {
+    assert_reports(&format!("{} func f(s: Shape): int {{ return match s {{ Shape::Circle(_) => 1 }}; }}", SHAPE), "Non-exhaustive match on `Shape`, missing `Shape::Rect(_, _)`, `Shape::Empty`");
+    assert_reports("enum Opt { Some(bool), None } func f(o: Opt): int { return match o { Opt::Some(true) => 1, Opt::None => 0 }; }", "missing `Opt::Some(false)`");
+    assert_reports("func f(n: int): int { return match n { 0 => 1, 1 => 2 }; }", "not every value of type `int` is covered, add a `_` arm");
+    assert_clean("func f(b: bool, n: int): int { var x: int = match b { true => 1, false => 0 }; return match n { 0 => 1, _ => x }; }");
+}
+
+#[test]
+fn unreachable_arms_and_bad_patterns_are_reported() {
+    assert_reports("enum C { A, B } func f(c: C): int { return match c { _ => 0, C::A => 1 }; }", "Unreachable match arm");
+    assert_reports("func f(n: int): int { return match n { 16 => 0, 0x10 => 1, _ => 2 }; }", "Unreachable match arm");
+    assert_reports(&format!("{} func f(s: Shape): int {{ return match s {{ Shape::Rect(w, w) => 1, _ => 0 }}; }}", SHAPE), "`w` is bound more than once in the same pattern");
+    assert_reports(&format!("enum C {{ A }} {} func f(s: Shape): int {{ return match s {{ C::A => 1, _ => 0 }}; }}", SHAPE), "the pattern is a `C` but the matched value is `Shape`");
+    assert_reports("func f(n: int): int { return match n { \"a\" => 1, _ => 0 }; }", "the pattern is `string` but the matched value is `int`");
+    assert_reports("func f(n: int): int { return match n { 1 => 1, _ => \"a\" }; }", "Match arms have incompatible types, expected `int` but this arm is `string`");
+}
+
+#[test]
+fn recursive_enums_are_reported() {
+    assert_reports("enum List { Cons(int, List), Nil }", "Recursive enum `List` has infinite size");
+    assert_clean("enum List { Cons(int, List*), Nil }");
+}
+
+#[test]
+fn every_path_must_return_a_value() {
+    assert_reports("func f(x: int): int { if (x > 0) { return 1; } }", "Not all paths of function `f` return a value of type `int`");
+    assert_reports("func f(x: int): int { while (x > 0) { return 1; } }", "Not all paths of function `f` return a value of type `int`");
+    assert_clean("func f(x: int): int { if (x > 0) { return 1; } else if (x < 0) { return -1; } else { return 0; } }");
+    assert_clean("func f(): int { while (true) {} }");
+    assert_clean("enum C { A, B } func f(c: C): int { match c { C::A => { return 1; } C::B => { return 2; } } }");
+    assert_clean("func f(): void { var x: int = 1; }");
+    assert_clean("func f(): int return 1;");
+}
+
+#[test]
+fn returns_in_nested_blocks_are_checked() {
+    assert_reports("func f(x: int): int { if (x > 0) { { return \"a\"; } } return 0; }", "Unexpected return type, expected `int` but got `string`");
+    assert_reports("func f(x: int): void { while (x > 0) { return x; } }", "Function `f` returns `void` and can't return a value");
+    assert_reports("func f(values: std::Array<int>): int { for (v: int in values) { return; } return 0; }", "Function `f` must return a value of type `int`");
+}
+
+#[test]
+fn code_after_a_return_is_unreachable() {
+    let contents: String = check("func f(x: int): int { return 1; x = 2; x = 3; }");
+    assert_eq!(contents.matches("Unreachable code").count(), 1, "{}", contents);
+    assert!(contents.contains("any code following this statement is unreachable"), "{}", contents);
+    assert_reports("func f(x: int): int { if (x > 0) { return 1; } else { return 2; } return 3; }", "Unreachable code");
+    assert_reports("func f(): int { while (true) {} return 1; }", "Unreachable code");
+    assert_clean("func f(x: int): int { while (x > 0) { return 1; } return 0; }");
+    // A match that misses values can skip every arm
+    let contents: String = check("enum E { A, B } func f(e: E): int { match e { E::A => { return 1; } } return 0; }");
+    assert!(contents.contains("Non-exhaustive match on `E`, missing `E::B`"), "{}", contents);
+    assert!(!contents.contains("Unreachable code"), "{}", contents);
+    assert_reports("enum E { A, B } func f(e: E): int { match e { E::A => { return 1; } E::B => { return 2; } } return 0; }", "Unreachable code");
+}
+
+#[test]
+fn scopes_with_the_same_name_dont_collide() {
+    assert_clean("namespace a { func a(x: int): int { return x; } } namespace b { func a(x: string): string { return x; } }
+        func f(): int { var s: string = b::a(\"s\"); return a::a(1); }");
+    let contents: String = check("func std(): int { return 0; } func f(v: std::Array<int>): int { return v.length; }");
+    assert!(contents.contains("Redefinition of function `std`"), "{}", contents);
+    assert!(!contents.contains("Unknown type"), "{}", contents);
+    assert_reports("func f(): int { return 1; } func f(): string { return 1; }", "expected `string` but got `int`");
+}
+
+#[test]
+fn shadowing_a_local_is_warned_about() {
+    assert_reports("func f(x: int): int { { var x: int = 2; } return x; }", "Variable `x` shadows an earlier declaration in the same function");
+    assert_reports("func f(): int { var y: int = 1; while (true) { var y: int = 2; } }", "Variable `y` shadows an earlier declaration in the same function");
+    // Later declarations, other functions, loop variables and pattern bindings don't count
+    assert_clean("func f(): int { { var x: int = 1; } var x: int = 2; return x; }");
+    assert_clean("func g(x: int): int { return x; } func f(): int { var x: int = 1; return x; }");
+    assert_clean("func f(x: int, v: std::Array<int>): int { for (x: int in v) {} return match x { x => x }; }");
+}
+
+#[test]
+fn names_are_bound_to_their_definitions() {
+    let source: &str = "namespace n { func g(): int { return 1; } } func f(x: int): int { { var x: int = 2; x = 3; } return x + n::g(); }";
+    let names: HashMap<usize, usize> = resolved_names(source);
+    assert_eq!(names.get(&source.find("x = 3").unwrap()).copied(), source.find("x: int = 2"));
+    assert_eq!(names.get(&source.find("x + ").unwrap()).copied(), source.find("x: int)"));
+    assert_eq!(names.get(&source.find("g();").unwrap()).copied(), source.find("g()"));
+}
+
+#[test]
+fn every_example_checks_without_an_ice() {
+    let directory: String = format!("{}/tests", env!("CARGO_MANIFEST_DIR"));
+    let mut examples: Vec<std::path::PathBuf> = std::fs::read_dir(&directory).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "comp"))
+        .collect();
+    examples.sort();
+    assert!(!examples.is_empty(), "no examples in `{}`", directory);
+    for example in examples {
+        let contents: String = check(&std::fs::read_to_string(&example).unwrap());
+        assert!(!contents.contains("Ice"), "internal compiler error checking `{}`: {}", example.display(), contents);
+        assert_eq!(contents, "", "unexpected diagnostics for `{}`", example.display());
+    }
+}
+
+#[test]
+fn calls_have_the_return_type_of_the_callee() {
+    assert_clean("namespace n { func g(): u8 { return 1; } } func f(): int { return n::g() + 1; }");
+    assert_reports("func g(): string { return \"a\"; } func f(): int { return g(); }", "Unexpected return type, expected `int` but got `string`");
+    assert_reports("func g(): void {} func f(): int { return -g(); }", "Invalid operand of type `void` to unary operator (`-`)");
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use common::{parser, printer};
+use programming_language::sema::checking::types::{binary_type, is_assignable, is_valid_cast};
+use programming_language::syntax::ast::TypeSpec;
+use programming_language::syntax::token::TokenType;
+
+fn pointer(pointee: TypeSpec) -> TypeSpec {
+    TypeSpec::Pointer(Box::new(pointee))
+}
+
+#[test]
+fn integers_only_widen_implicitly() {
+    assert!(is_assignable(&TypeSpec::U8, &TypeSpec::I16));
+    assert!(is_assignable(&TypeSpec::I32, &TypeSpec::Int));
+    assert!(is_assignable(&TypeSpec::U32, &TypeSpec::U64));
+    assert!(!is_assignable(&TypeSpec::I8, &TypeSpec::U64));
+    assert!(!is_assignable(&TypeSpec::U64, &TypeSpec::Int));
+    assert!(!is_assignable(&TypeSpec::I64, &TypeSpec::I32));
+    assert!(!is_assignable(&TypeSpec::Bool, &TypeSpec::Int));
+    assert!(is_assignable(&TypeSpec::Null, &pointer(TypeSpec::U8)));
+    assert!(is_assignable(&TypeSpec::F32, &TypeSpec::F64));
+    assert!(!is_assignable(&TypeSpec::F64, &TypeSpec::F32));
+    assert!(!is_assignable(&TypeSpec::I8, &TypeSpec::F64));
+}
+
+#[test]
+fn cast_table() {
+    let legal: &[(TypeSpec, TypeSpec)] = &[
+        (TypeSpec::I64, TypeSpec::U8), (TypeSpec::U8, TypeSpec::I8), (TypeSpec::Bool, TypeSpec::U16),
+        (TypeSpec::Char, TypeSpec::U32), (TypeSpec::I32, TypeSpec::Char), (pointer(TypeSpec::Int), TypeSpec::U64),
+        (TypeSpec::Int, pointer(TypeSpec::Char)), (pointer(TypeSpec::Int), pointer(TypeSpec::U8)),
+        (TypeSpec::F64, TypeSpec::U8), (TypeSpec::I16, TypeSpec::F32), (TypeSpec::F64, TypeSpec::F32),
+    ];
+    for (from, to) in legal {
+        assert!(is_valid_cast(from, to), "`{}` as `{}`", from, to);
+    }
+    let illegal: &[(TypeSpec, TypeSpec)] = &[
+        (TypeSpec::String, TypeSpec::Int), (TypeSpec::Int, TypeSpec::Bool), (TypeSpec::Char, TypeSpec::Bool),
+        (pointer(TypeSpec::Int), TypeSpec::I32), (TypeSpec::U8, pointer(TypeSpec::U8)), (TypeSpec::Int, TypeSpec::String),
+        (TypeSpec::F32, TypeSpec::Bool), (pointer(TypeSpec::Int), TypeSpec::F64),
+    ];
+    for (from, to) in illegal {
+        assert!(!is_valid_cast(from, to), "`{}` as `{}`", from, to);
+    }
+}
+
+#[test]
+fn binary_operators_widen_and_compare_to_bool() {
+    assert_eq!(binary_type(TokenType::Plus, &TypeSpec::U8, &TypeSpec::I32), Some(TypeSpec::I32));
+    assert_eq!(binary_type(TokenType::Plus, &TypeSpec::I8, &TypeSpec::U64), None);
+    assert_eq!(binary_type(TokenType::Less, &TypeSpec::U16, &TypeSpec::U32), Some(TypeSpec::Bool));
+    assert_eq!(binary_type(TokenType::LessLess, &TypeSpec::U8, &TypeSpec::I64), Some(TypeSpec::U8));
+    assert_eq!(binary_type(TokenType::AmpersandAmpersand, &TypeSpec::Bool, &TypeSpec::Int), Some(TypeSpec::Bool));
+    assert_eq!(binary_type(TokenType::Plus, &TypeSpec::Bool, &TypeSpec::Bool), None);
+    assert_eq!(binary_type(TokenType::EqualEqual, &pointer(TypeSpec::Int), &TypeSpec::Null), Some(TypeSpec::Bool));
+    assert_eq!(binary_type(TokenType::Plus, &pointer(TypeSpec::Int), &TypeSpec::U8), Some(pointer(TypeSpec::Int)));
+    assert_eq!(binary_type(TokenType::Star, &TypeSpec::F32, &TypeSpec::F64), Some(TypeSpec::F64));
+    assert_eq!(binary_type(TokenType::Less, &TypeSpec::F32, &TypeSpec::F32), Some(TypeSpec::Bool));
+    assert_eq!(binary_type(TokenType::Plus, &TypeSpec::F32, &TypeSpec::Int), None);
+    assert_eq!(binary_type(TokenType::Pipe, &TypeSpec::F64, &TypeSpec::F64), None);
+}
+
+#[test]
+fn written_types_print_the_way_they_are_written() {
+    for written in ["u8", "int**", "std::string", "geo::Point*", "std::Array<u8*>", "Pair<i8, std::Array<f64>>"] {
+        let (diag, sink) = printer(written, 0);
+        let type_spec: TypeSpec = parser(written, &diag).parse_type_annotation();
+        assert_eq!(sink.contents(), "", "unexpected diagnostics for `{}`", written);
+        assert_eq!(type_spec.to_string(), written);
+    }
+    let (diag, _) = printer("std::Array<u8*>", 0);
+    assert_eq!(parser("std::Array<u8*>", &diag).parse_type_annotation(), TypeSpec::Generic("std::Array".to_string(), vec![pointer(TypeSpec::U8)]));
+}